@@ -1,106 +1,892 @@
 use std::io;
-use crate::{context_view::ContextView, provider_view::ProviderView, traits::View};
-use crossterm::event::{self, Event, KeyCode};
-use ratatui::Terminal;
-use std::time::Duration;
-use ratatui::layout::{Constraint, Direction, Layout};
-use ratatui::widgets::{Block, Borders, Paragraph};
-use ratatui::style::{Style, Color, Modifier};
+use crate::{
+    chat::{self, ChatRequest}, clipboard, config, editor::{self, EditTarget}, env::Env, help::{HelpOverlay, Section}, keymap::{KeyChord, Keymap, Resolution}, layout::{LayoutConfig, Panes}, mouse, notify::Notifications, palette::{self, Entry, Outcome, Palette, Target}, registry::ViewRegistry, store::{ProviderStore, SharedStore}, terminal::{self, Signals, Tui},
+    theme::{ThemeConfig, Themes}, traits::{Action, Status, Update, View}, usage::{SharedLedger, UsageLedger}, widgets::TextInput,
+};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::Frame;
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::widgets::Paragraph;
+use ratatui::style::Modifier;
 use ratatui::text::{Line, Span};
 
-#[derive(Clone)]
-pub enum AppView {
-    ContextView,
-    ProviderView,
-}
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+/// Handled before the keymap so they work in every view, input and overlay; not rebindable.
+const FIXED_KEYS: [(&str, &str); 2] = [("ctrl-c", "Quit"), ("ctrl-z", "Suspend")];
 
 pub struct App {
-    views: Vec<Box<dyn View>>,
-    active_view: usize,
-    command_input: String,
-    info_message: String,
-    current_view: AppView,
+    views: ViewRegistry,
+    panes: Panes,
+    layout_path: Option<PathBuf>,
+    pane_areas: RefCell<Vec<Rect>>,
+    dragging: bool,
+    command_input: TextInput,
+    notifications: Notifications,
+    ticks: usize,
+    keymap: Keymap,
+    pending_keys: Vec<KeyChord>,
+    themes: Themes,
+    theme_path: Option<PathBuf>,
+    tabs: RefCell<Vec<Rect>>,
+    palette: Option<Palette>,
+    help: Option<HelpOverlay>,
+    suspend_requested: bool,
+    edit_requested: Option<(usize, EditTarget)>,
+    update_sender: Sender<(usize, Update)>,
+    updates: Receiver<(usize, Update)>,
 }
 
 impl App {
     pub fn new() -> Self {
-        Self {
-            views: vec![
-                Box::new(ContextView::new()),
-                Box::new(ProviderView::new()),
-            ],
-            active_view: 0,
-            command_input: String::new(),
-            info_message: String::new(),
-            current_view: AppView::ContextView,
+        let store = ProviderStore::load(config::providers_path(), config::vault_path(), Env::load()).shared();
+        let errors: Vec<_> = {
+            let store = store.borrow();
+            store.config_error.iter().chain(&store.vault_error).cloned().collect()
+        };
+        let ledger = UsageLedger::load(&config::usage_config_path(), config::usage_log_path()).shared();
+        let (keymap, keymap_warnings) = Keymap::load(&config::keymap_path());
+        let selected = ThemeConfig::load(&config::theme_path()).unwrap_or_default().theme;
+        let (themes, theme_warnings) = Themes::load(&config::themes_dir(), selected.as_deref());
+        let layout = LayoutConfig::load(&config::layout_path()).unwrap_or_default();
+        let mut app = Self::with_store(store, ledger, keymap);
+        app.themes = themes;
+        app.theme_path = Some(config::theme_path());
+        app.restore_layout(&layout);
+        app.layout_path = Some(config::layout_path());
+        let mut messages = Vec::new();
+        if !keymap_warnings.is_empty() {
+            messages.push(format!("Keymap: {}", keymap_warnings.join("; ")));
+        }
+        if !theme_warnings.is_empty() {
+            messages.push(format!("Themes: {}", theme_warnings.join("; ")));
+        }
+        for message in messages {
+            app.notifications.warning(message);
+        }
+        for error in errors {
+            app.notifications.error(error);
+        }
+        app
+    }
+
+    pub fn with_store(store: SharedStore, ledger: SharedLedger, keymap: Keymap) -> Self {
+        Self::with_views(ViewRegistry::with_defaults(store, ledger), keymap)
+    }
+
+    pub fn with_views(mut views: ViewRegistry, mut keymap: Keymap) -> Self {
+        for view in views.iter() {
+            keymap.bind_default("global", &format!("show_{}", view.shortcut()), &view.shortcut().to_string());
         }
+        for index in 0..views.len() {
+            views.get_mut(index).set_keymap(&keymap);
+        }
+        let conflicts = keymap.conflicts();
+        let (update_sender, updates) = mpsc::channel();
+        let mut app = Self {
+            views,
+            panes: Panes::default(),
+            layout_path: None,
+            pane_areas: RefCell::new(Vec::new()),
+            dragging: false,
+            command_input: TextInput::new(),
+            notifications: Notifications::default(),
+            ticks: 0,
+            keymap,
+            pending_keys: Vec::new(),
+            themes: Themes::default(),
+            theme_path: None,
+            tabs: RefCell::new(Vec::new()),
+            palette: None,
+            help: None,
+            suspend_requested: false,
+            edit_requested: None,
+            update_sender,
+            updates,
+        };
+        if !conflicts.is_empty() {
+            app.notifications.warning(format!("Keymap conflicts: {}", conflicts.join("; ")));
+        }
+        app
     }
 
-    pub fn run<B: ratatui::backend::Backend>(&mut self, mut terminal: Terminal<B>) -> Result<(), io::Error> {
+    pub fn run(&mut self, mut terminal: Tui, signals: &Signals) -> Result<(), io::Error> {
         loop {
-            terminal.draw(|f| {
-                let size = f.size();
-                let active_style = Style::default().fg(Color::Green).add_modifier(Modifier::BOLD);
-                let inactive_style = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
-
-                let layout = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints(
-                        [
-                            Constraint::Length(3),
-                            Constraint::Min(0),
-                        ]
-                            .as_ref(),
-                    )
-                    .split(size);
-
-                let header_line = Line::from(vec![
-                    Span::styled("Context View [1]", if matches!(self.current_view, AppView::ContextView) { active_style } else { inactive_style }),
-                    Span::styled(" | ", Style::default().fg(Color::White)),
-                    Span::styled("Provider View [2]", if matches!(self.current_view, AppView::ProviderView) { active_style } else { inactive_style }),
-                ]);
-                let header = Paragraph::new(header_line)
-                    .block(Block::default().borders(Borders::ALL).title("Header"));
-                f.render_widget(header, layout[0]);
-
-                self.views[self.active_view].render(f, layout[1], &self.info_message);
-            })?;
+            if signals.terminate_requested() {
+                break;
+            }
+            if signals.take_suspend() || std::mem::take(&mut self.suspend_requested) {
+                terminal::suspend(&mut terminal)?;
+            }
+            if let Some((view, target)) = self.edit_requested.take() {
+                let outcome = terminal::suspended(&mut terminal, || {
+                    signals.detached(|| editor::edit(&editor::command(), &target))
+                })?;
+                let action = self.views.get_mut(view).update(Update::Edited(outcome));
+                if !self.apply(view, action) {
+                    break;
+                }
+            }
+            terminal.draw(|f| self.draw(f))?;
 
             if event::poll(Duration::from_millis(100))? {
-                if let Event::Key(key) = event::read()? {
-                    match key.code {
-                        KeyCode::Char(':') => {
-                            self.command_input.push(':');
-                        }
-                        KeyCode::Char(c) if self.command_input.starts_with(':') => {
-                            self.command_input.push(c);
-                        }
-                        KeyCode::Enter if self.command_input.starts_with(':') => {
-                            self.command_input.clear();
-                        }
-                        KeyCode::Tab => {
-                            self.active_view = (self.active_view + 1) % self.views.len();
-                            self.current_view = match self.active_view {
-                                0 => AppView::ContextView,
-                                1 => AppView::ProviderView,
-                                _ => self.current_view.clone(),
-                            };
-                        }
-                        KeyCode::Char('1') => {
-                            self.active_view = 0;
-                            self.current_view = AppView::ContextView;
-                        }
-                        KeyCode::Char('2') => {
-                            self.active_view = 1;
-                            self.current_view = AppView::ProviderView;
+                let running = match event::read()? {
+                    Event::Key(key) => self.handle_key(key),
+                    Event::Mouse(mouse) => self.handle_mouse(mouse),
+                    Event::Paste(text) => {
+                        self.handle_paste(&text);
+                        true
+                    }
+                    Event::Resize(..) => {
+                        self.dragging = false;
+                        true
+                    }
+                    _ => true,
+                };
+                if !running {
+                    break;
+                }
+            }
+            if !self.tick() {
+                break;
+            }
+        }
+        self.save_layout()
+    }
+
+    fn shortcuts(&self) -> Vec<char> {
+        self.views.iter().map(|view| view.shortcut()).collect()
+    }
+
+    pub fn restore_layout(&mut self, layout: &LayoutConfig) {
+        self.panes = Panes::from_config(layout, &self.shortcuts());
+        for (shortcut, split) in &layout.view_splits {
+            if let Some(index) = self.views.by_shortcut(*shortcut) {
+                self.views.get_mut(index).set_split(*split);
+            }
+        }
+    }
+
+    pub fn layout(&self) -> LayoutConfig {
+        let mut layout = self.panes.to_config(&self.shortcuts());
+        layout.view_splits = self.views.iter().filter_map(|view| view.split().map(|split| (view.shortcut(), split))).collect();
+        layout
+    }
+
+    pub fn save_layout(&self) -> Result<(), io::Error> {
+        match &self.layout_path {
+            Some(path) => self.layout().save(path),
+            None => Ok(()),
+        }
+    }
+
+    pub fn draw(&self, f: &mut Frame) {
+        let size = f.area();
+        let theme = self.themes.active();
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(size);
+        let mut tabs = self.tabs.borrow_mut();
+        tabs.clear();
+        if self.panes.zoomed {
+            self.pane_areas.replace(vec![rows[0]]);
+            self.views.get(self.panes.active()).render(f, rows[0], theme);
+        } else {
+            self.draw_panes(f, rows[0], &mut tabs);
+        }
+        self.draw_status(f, rows[1]);
+        self.notifications.render(f, rows[0], theme);
+        if let Some(help) = &self.help {
+            help.render(f, size, theme);
+        }
+        if let Some(palette) = &self.palette {
+            palette.render(f, size, theme);
+        }
+    }
+
+    fn draw_panes(&self, f: &mut Frame, size: Rect, tabs: &mut Vec<Rect>) {
+        let theme = self.themes.active();
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(3),
+                    Constraint::Min(0),
+                ]
+                    .as_ref(),
+            )
+            .split(size);
+
+        let mut header_spans = Vec::new();
+        let mut x = layout[0].x + 1;
+        for (index, view) in self.views.iter().enumerate() {
+            if index > 0 {
+                header_spans.push(Span::styled(" | ", theme.text));
+                x += 3;
+            }
+            let tab = Span::styled(
+                format!("{} [{}]", view.title(), view.shortcut()),
+                if index == self.panes.active() {
+                    theme.header_active
+                } else if self.panes.views().contains(&index) {
+                    theme.header_inactive.add_modifier(Modifier::UNDERLINED)
+                } else {
+                    theme.header_inactive
+                },
+            );
+            tabs.push(Rect { x, y: layout[0].y + 1, width: tab.width() as u16, height: 1 }.intersection(layout[0]));
+            x += tab.width() as u16;
+            header_spans.push(tab);
+        }
+        let active = self.views.get(self.panes.active());
+        let header = Paragraph::new(Line::from(header_spans))
+            .block(theme.block("Header").title_bottom(self.keymap.hints(active.keymap_scope(), active.hints())));
+        f.render_widget(header, layout[0]);
+
+        let areas = self.panes.areas(layout[1]);
+        for (view, area) in self.panes.visible().into_iter().zip(&areas) {
+            self.views.get(view).render(f, *area, theme);
+        }
+        self.pane_areas.replace(areas);
+    }
+
+    fn status(&self) -> Status {
+        let mut views: Vec<&dyn View> = vec![self.views.get(self.panes.active())];
+        views.extend(self.views.iter());
+        views.into_iter().map(|view| view.status()).fold(Status::default(), |status, view| Status {
+            provider: status.provider.or(view.provider),
+            model: status.model.or(view.model),
+            context_tokens: status.context_tokens.or(view.context_tokens),
+            busy: status.busy || view.busy,
+        })
+    }
+
+    fn draw_status(&self, f: &mut Frame, area: Rect) {
+        let theme = self.themes.active();
+        if !self.command_input.value().is_empty() {
+            self.command_input.render(f, area, theme.text, true);
+            return;
+        }
+        let status = self.status();
+        let mut spans = vec![Span::styled(
+            if status.busy { format!(" {} ", SPINNER[self.ticks % SPINNER.len()]) } else { "   ".to_string() },
+            theme.accent,
+        )];
+        let fields = [
+            ("Provider", status.provider),
+            ("Model", status.model),
+            ("Context", status.context_tokens.map(|tokens| format!("{} tokens", tokens))),
+        ];
+        for (label, value) in fields {
+            spans.push(Span::styled(format!("{}: ", label), theme.muted));
+            spans.push(Span::styled(format!("{}  ", value.as_deref().unwrap_or("-")), theme.text));
+        }
+        f.render_widget(Paragraph::new(Line::from(spans)), area);
+        if let Some(latest) = self.notifications.latest() {
+            let pending = match self.notifications.count() {
+                1 => String::new(),
+                count => format!(" (+{})", count - 1),
+            };
+            let summary = Line::from(vec![
+                Span::styled(format!("{}{} ", latest.severity.label(), pending), latest.severity.style(theme)),
+            ]);
+            f.render_widget(Paragraph::new(summary).alignment(Alignment::Right), area);
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+        if key.code == KeyCode::Char('z') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.suspend_requested = true;
+            return true;
+        }
+        if let Some(palette) = &mut self.palette {
+            let binding = match self.keymap.feed(&mut self.pending_keys, &["palette"], key) {
+                Resolution::Pending => return true,
+                Resolution::Bound(binding) => Some(binding.action.as_str()),
+                Resolution::Unbound => None,
+            };
+            match palette.handle_input(key, binding) {
+                Outcome::Open => {}
+                Outcome::Closed => self.palette = None,
+                Outcome::Run(target) => {
+                    self.palette = None;
+                    return self.run_target(target);
+                }
+            }
+            return true;
+        }
+        if let Some(help) = &mut self.help {
+            let binding = match self.keymap.feed(&mut self.pending_keys, &["help"], key) {
+                Resolution::Pending => return true,
+                Resolution::Bound(binding) => Some(binding.action.as_str()),
+                Resolution::Unbound => None,
+            };
+            if !help.handle_input(key, binding) {
+                self.help = None;
+            }
+            return true;
+        }
+        if !self.command_input.value().is_empty() {
+            match key.code {
+                KeyCode::Enter => {
+                    let command = self.command_input.take();
+                    let mut words = command.split_whitespace();
+                    match (words.next(), words.next()) {
+                        (Some(":q" | ":quit"), _) => return self.apply(self.panes.active(), Action::Quit),
+                        (Some(":theme"), Some(name)) => self.set_theme(Some(name)),
+                        (Some(":theme"), None) => {
+                            self.notifications.info(format!("Themes: {}", self.themes.names().collect::<Vec<_>>().join(", ")))
                         }
-                        KeyCode::Esc => break,
-                        _ => self.views[self.active_view].handle_input(key, &mut self.info_message),
+                        _ => {}
+                    }
+                }
+                KeyCode::Esc => {
+                    self.command_input.take();
+                }
+                _ => {
+                    self.command_input.handle_input(key, None);
+                }
+            }
+            return true;
+        }
+
+        let view = self.views.get(self.panes.active());
+        let scopes = if view.wants_raw_input() { vec![view.keymap_scope()] } else { vec![view.keymap_scope(), "global"] };
+        let binding = match self.keymap.feed(&mut self.pending_keys, &scopes, key) {
+            Resolution::Pending => return true,
+            Resolution::Bound(binding) => Some((binding.scope == "global", binding.action.clone())),
+            Resolution::Unbound => None,
+        };
+        match binding {
+            Some((true, action)) => self.run_global(&action),
+            binding => {
+                let binding = binding.map(|(_, action)| action);
+                let action = self.views.get_mut(self.panes.active()).handle_input(key, binding.as_deref(), &mut self.notifications);
+                self.apply(self.panes.active(), action)
+            }
+        }
+    }
+
+    pub fn handle_paste(&mut self, text: &str) {
+        if let Some(palette) = &mut self.palette {
+            palette.handle_paste(text);
+        } else if let Some(help) = &mut self.help {
+            help.handle_paste(text);
+        } else if !self.command_input.value().is_empty() {
+            self.command_input.insert_str(text);
+        } else {
+            self.views.get_mut(self.panes.active()).handle_paste(text);
+        }
+    }
+
+    fn run_global(&mut self, action: &str) -> bool {
+        match action {
+            "next_view" => self.switch_to((self.panes.active() + 1) % self.views.len()),
+            "quit" => return self.apply(self.panes.active(), Action::Quit),
+            "command" => self.command_input.set_value(":"),
+            "next_theme" => self.set_theme(None),
+            "split_pane" => {
+                let count = self.views.len();
+                self.change_panes(|panes| panes.toggle_split(count));
+            }
+            "focus_pane" => self.change_panes(Panes::cycle_focus),
+            "grow_pane" => self.panes.resize(5),
+            "shrink_pane" => self.panes.resize(-5),
+            "zoom_pane" => {
+                self.panes.zoomed = !self.panes.zoomed;
+                self.dragging = false;
+            }
+            "palette" => self.palette = Some(Palette::new(self.palette_entries())),
+            "help" => {
+                let hints = self.keymap.hints("help", &[("close", "Close"), ("scroll_up", "Scroll up"), ("scroll_down", "Scroll down")]);
+                self.help = Some(HelpOverlay::new(hints, self.help_sections()));
+            }
+            "dismiss_notification" => self.notifications.dismiss(),
+            _ => {
+                if let Some(shortcut) = action.strip_prefix("show_").and_then(|shortcut| shortcut.chars().next()) {
+                    return self.apply(self.panes.active(), Action::Navigate(shortcut));
+                }
+            }
+        }
+        true
+    }
+
+    fn global_label(&self, action: &str) -> Option<String> {
+        match action.strip_prefix("show_").and_then(|shortcut| shortcut.chars().next()) {
+            Some(shortcut) => self.views.by_shortcut(shortcut).map(|index| format!("Show {}", self.views.get(index).title())),
+            None => Some(palette::humanize(action)),
+        }
+    }
+
+    fn help_sections(&self) -> Vec<Section> {
+        let view = self.views.get(self.panes.active());
+        let keys = |scope: &str| {
+            self.keymap
+                .actions(scope)
+                .into_iter()
+                .filter_map(|(action, keys)| {
+                    let label = if scope == "global" { self.global_label(action)? } else { palette::humanize(action) };
+                    Some((keys.join(", "), label))
+                })
+                .collect()
+        };
+        let mut global: Vec<(String, String)> = keys("global");
+        global.extend(FIXED_KEYS.iter().map(|(keys, label)| (keys.to_string(), label.to_string())));
+        vec![
+            Section { title: view.title().to_string(), keys: keys(view.keymap_scope()) },
+            Section { title: "Global".to_string(), keys: global },
+        ]
+    }
+
+    fn palette_entries(&self) -> Vec<Entry> {
+        let mut entries = Vec::new();
+        for (action, keys) in self.keymap.actions("global") {
+            if action == "palette" {
+                continue;
+            }
+            let Some(label) = self.global_label(action) else {
+                continue;
+            };
+            entries.push(Entry { label, hint: keys.join(", "), target: Target::Global(action.to_string()) });
+        }
+        for (index, view) in self.views.iter().enumerate() {
+            for (action, keys) in self.keymap.actions(view.keymap_scope()) {
+                entries.push(Entry {
+                    label: format!("{}: {}", view.title(), palette::humanize(action)),
+                    hint: keys.join(", "),
+                    target: Target::Action { view: index, action: action.to_string() },
+                });
+            }
+        }
+        for (index, view) in self.views.iter().enumerate() {
+            for entry in view.palette_entries() {
+                entries.push(Entry {
+                    label: entry.label,
+                    hint: view.title().to_string(),
+                    target: Target::Command { view: index, command: entry.command },
+                });
+            }
+        }
+        entries
+    }
+
+    fn run_target(&mut self, target: Target) -> bool {
+        match target {
+            Target::Global(action) => self.run_global(&action),
+            Target::Action { view, action } => {
+                self.switch_to(view);
+                let key = KeyEvent::new(KeyCode::Null, KeyModifiers::NONE);
+                let action = self.views.get_mut(view).handle_input(key, Some(&action), &mut self.notifications);
+                self.apply(view, action)
+            }
+            Target::Command { view, command } => {
+                self.switch_to(view);
+                let action = self.views.get_mut(view).run_command(&command, &mut self.notifications);
+                self.apply(view, action)
+            }
+        }
+    }
+
+    pub fn handle_mouse(&mut self, event: MouseEvent) -> bool {
+        if self.palette.is_some() || self.help.is_some() {
+            if matches!(event.kind, MouseEventKind::Down(_)) {
+                self.palette = None;
+                self.help = None;
+            }
+            return true;
+        }
+        let tab = self.tabs.borrow().iter().position(|tab| mouse::clicked(*tab, &event));
+        if let Some(index) = tab {
+            self.switch_to(index);
+            return true;
+        }
+        let areas = self.pane_areas.borrow().clone();
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) if areas.len() > 1 && areas[1].x == event.column && mouse::hit(areas[1], &event) => {
+                self.dragging = true;
+                return true;
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.dragging && areas.len() == 2 => {
+                let area = areas[0].union(areas[1]);
+                self.panes.set_split(((event.column.saturating_sub(area.x) as u32 * 100) / area.width.max(1) as u32) as u16);
+                return true;
+            }
+            MouseEventKind::Up(MouseButton::Left) if self.dragging => {
+                self.dragging = false;
+                return true;
+            }
+            MouseEventKind::Down(_) | MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+                if let Some(pane) = areas.iter().position(|area| mouse::hit(*area, &event)) {
+                    if matches!(event.kind, MouseEventKind::Down(_)) {
+                        self.change_panes(|panes| panes.focus(pane));
+                    } else {
+                        let view = self.panes.visible()[pane];
+                        let action = self.views.get_mut(view).handle_mouse(event, &mut self.notifications);
+                        return self.apply(view, action);
                     }
                 }
             }
+            _ => {}
         }
-        Ok(())
+        let action = self.views.get_mut(self.panes.active()).handle_mouse(event, &mut self.notifications);
+        self.apply(self.panes.active(), action)
+    }
+
+    pub fn tick(&mut self) -> bool {
+        self.ticks = self.ticks.wrapping_add(1);
+        self.notifications.expire(Instant::now());
+        while let Ok((index, update)) = self.updates.try_recv() {
+            let action = self.views.get_mut(index).update(update);
+            if !self.apply(index, action) {
+                return false;
+            }
+        }
+        for index in 0..self.views.len() {
+            let action = self.views.get_mut(index).tick();
+            if !self.apply(index, action) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn apply(&mut self, view: usize, action: Action) -> bool {
+        match action {
+            Action::None => {}
+            Action::Copy(text) => match clipboard::copy_to_system(&text) {
+                Ok(method) => self.notifications.success(format!("Copied {} lines via {}", text.lines().count(), method)),
+                Err(e) => self.notifications.error(format!("Copy failed: {}", e)),
+            },
+            Action::Edit(target) => self.edit_requested = Some((view, target)),
+            Action::Navigate(shortcut) => {
+                if let Some(index) = self.views.by_shortcut(shortcut) {
+                    self.switch_to(index);
+                }
+            }
+            Action::Notify(severity, message) => self.notifications.push(severity, message),
+            Action::Quit => return false,
+            Action::Send(request) => self.spawn(view, request),
+        }
+        true
+    }
+
+    fn set_theme(&mut self, name: Option<&str>) {
+        match name {
+            Some(name) if !self.themes.select(name) => {
+                self.notifications.error(format!("Unknown theme {}", name));
+                return;
+            }
+            Some(_) => {}
+            None => {
+                self.themes.next();
+            }
+        }
+        let name = self.themes.active().name.clone();
+        self.notifications.info(format!("Theme: {}", name));
+        if let Some(path) = &self.theme_path {
+            if let Err(e) = (ThemeConfig { theme: Some(name) }).save(path) {
+                self.notifications.error(format!("Failed to save theme: {}", e));
+            }
+        }
+    }
+
+    fn switch_to(&mut self, index: usize) {
+        self.change_panes(|panes| panes.show(index));
+    }
+
+    fn change_panes(&mut self, change: impl FnOnce(&mut Panes)) {
+        let previous = self.panes.active();
+        change(&mut self.panes);
+        self.dragging = false;
+        if self.panes.active() != previous {
+            self.views.get_mut(previous).on_leave();
+            self.views.get_mut(self.panes.active()).on_enter();
+        }
+    }
+
+    fn spawn(&self, view: usize, request: ChatRequest) {
+        let sender = self.update_sender.clone();
+        thread::spawn(move || {
+            let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+                chat::dispatch(&request.dispatch, &request.messages, &mut |event| {
+                    let _ = sender.send((view, Update::Chat(event)));
+                })
+            }))
+            .unwrap_or_else(|_| Err(format!("request failed unexpectedly, see {}", config::crash_dir().display())));
+            let _ = sender.send((view, Update::Finished(outcome)));
+        });
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::App;
+    use crate::layout::LayoutConfig;
+    use crate::notify::Notifications;
+    use crate::provider::{ProviderInstance, ProviderSettings, ProviderType};
+    use crate::keymap::Keymap;
+    use crate::registry::ViewRegistry;
+    use crate::snapshot::{self, click, ctrl, key, mouse, Harness};
+    use crate::theme::Theme;
+    use crate::traits::{Action, View};
+    use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEventKind};
+    use ratatui::layout::Rect;
+    use ratatui::widgets::Paragraph;
+    use ratatui::Frame;
+    use std::fs;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn switches_views_from_header_shortcuts() {
+        let (store, ledger) = snapshot::store("app-switch");
+        let mut harness = Harness::new(App::with_store(store, ledger, Keymap::default()), 100, 12);
+        harness
+            .assert_snapshot("app_context_view")
+            .keys(&[key(KeyCode::Char('2'))])
+            .assert_snapshot("app_provider_view")
+            .keys(&[key(KeyCode::Tab)])
+            .assert_snapshot("app_chat_view")
+            .keys(&[key(KeyCode::Esc), key(KeyCode::Tab), key(KeyCode::Tab), key(KeyCode::Tab)])
+            .assert_snapshot("app_context_view");
+    }
+
+    #[test]
+    fn switches_views_by_clicking_tabs() {
+        let (store, ledger) = snapshot::store("app-click");
+        let mut harness = Harness::new(App::with_store(store, ledger, Keymap::default()), 100, 12);
+        harness
+            .mouse(&[click(22, 1)])
+            .assert_snapshot("app_provider_view")
+            .mouse(&[click(19, 1)])
+            .assert_snapshot("app_provider_view")
+            .mouse(&[click(1, 1)])
+            .assert_snapshot("app_context_view");
+    }
+
+    #[test]
+    fn splits_resizes_and_zooms_panes() {
+        let (store, ledger) = snapshot::store("app-panes");
+        let mut harness = Harness::new(App::with_store(store, ledger, Keymap::default()), 100, 12);
+        harness
+            .keys(&[ctrl('w'), key(KeyCode::Char('v'))])
+            .assert_snapshot("app_split")
+            .keys(&[ctrl('w'), key(KeyCode::Char('w')), ctrl('w'), key(KeyCode::Char('>'))])
+            .assert_snapshot("app_split_resized")
+            .keys(&[ctrl('w'), key(KeyCode::Char('z'))])
+            .assert_snapshot("app_zoomed")
+            .keys(&[ctrl('w'), key(KeyCode::Char('z'))])
+            .mouse(&[
+                mouse(MouseEventKind::Down(MouseButton::Left), 45, 6),
+                mouse(MouseEventKind::Drag(MouseButton::Left), 50, 6),
+                mouse(MouseEventKind::Up(MouseButton::Left), 50, 6),
+            ])
+            .assert_snapshot("app_split_dragged");
+
+        let layout = harness.subject.layout();
+        assert_eq!(layout.panes, vec!['1', '2']);
+        assert_eq!(layout.focus, 1);
+        assert_eq!(layout.view_splits.get(&'1'), Some(&15));
+    }
+
+    #[test]
+    fn abandons_divider_drag_when_panes_change() {
+        let (store, ledger) = snapshot::store("app-drag-zoom");
+        let mut harness = Harness::new(App::with_store(store, ledger, Keymap::default()), 100, 12);
+        harness
+            .keys(&[ctrl('w'), key(KeyCode::Char('v'))])
+            .mouse(&[mouse(MouseEventKind::Down(MouseButton::Left), 50, 6)]);
+        assert!(harness.subject.dragging);
+        harness
+            .keys(&[ctrl('w'), key(KeyCode::Char('z'))])
+            .mouse(&[mouse(MouseEventKind::Drag(MouseButton::Left), 70, 6)]);
+        assert!(!harness.subject.dragging);
+
+        harness
+            .keys(&[ctrl('w'), key(KeyCode::Char('z'))])
+            .mouse(&[mouse(MouseEventKind::Down(MouseButton::Left), 50, 6)])
+            .keys(&[ctrl('w'), key(KeyCode::Char('v'))])
+            .mouse(&[mouse(MouseEventKind::Drag(MouseButton::Left), 70, 6)]);
+        assert!(!harness.subject.dragging);
+        assert_eq!(harness.subject.layout().split, 50);
+    }
+
+    #[test]
+    fn restores_saved_layout() {
+        let path = snapshot::temp_dir("app-layout").join("layout.yaml");
+        LayoutConfig { panes: vec!['3', '1'], focus: 1, split: 60, zoomed: false, view_splits: [('1', 50)].into() }
+            .save(&path)
+            .unwrap();
+        let (store, ledger) = snapshot::store("app-layout-restore");
+        let mut app = App::with_store(store, ledger, Keymap::default());
+        app.restore_layout(&LayoutConfig::load(&path).unwrap());
+        assert_eq!(app.layout(), LayoutConfig::load(&path).unwrap());
+        Harness::new(app, 100, 12).assert_snapshot("app_restored_layout");
+    }
+
+    #[test]
+    fn runs_actions_and_commands_from_palette() {
+        let (store, ledger) = snapshot::store("app-palette");
+        let mut harness = Harness::new(App::with_store(store, ledger, Keymap::default()), 100, 20);
+        harness
+            .keys(&[ctrl('p')])
+            .type_text("file3")
+            .assert_snapshot("app_palette_filtered")
+            .keys(&[key(KeyCode::Enter)])
+            .assert_snapshot("app_palette_opened_file")
+            .keys(&[ctrl('p')])
+            .type_text("pvadd")
+            .keys(&[key(KeyCode::Enter)]);
+        assert!(harness.render().contains("Provider Settings"));
+
+        harness.keys(&[key(KeyCode::Esc), ctrl('p')]).type_text("zzz").keys(&[key(KeyCode::Enter)]);
+        assert!(harness.subject.palette.is_none());
+        harness.keys(&[ctrl('p'), key(KeyCode::Esc)]);
+        assert!(harness.subject.palette.is_none());
+    }
+
+    #[test]
+    fn help_lists_current_view_and_global_keys() {
+        let (store, ledger) = snapshot::store("app-help");
+        let mut harness = Harness::new(App::with_store(store, ledger, Keymap::preset("vim").unwrap()), 100, 36);
+        harness
+            .keys(&[key(KeyCode::Char('?'))])
+            .assert_snapshot("app_help")
+            .type_text("thm")
+            .assert_snapshot("app_help_search")
+            .keys(&[key(KeyCode::Esc)]);
+        assert!(harness.subject.help.is_none());
+    }
+
+    #[test]
+    fn chat_reply_arrives_in_background() {
+        let (store, ledger) = snapshot::store("app-chat");
+        let fixture = snapshot::temp_dir("app-chat-fixture").join("fixture.yaml");
+        fs::write(&fixture, "responses:\n  - chunks: [\"Hello\", \" there\"]\n    usage: {prompt_tokens: 2, completion_tokens: 2}\n").unwrap();
+        store.borrow_mut().update(
+            ProviderInstance { name: "mock".to_string(), provider_type: ProviderType::Mock },
+            ProviderSettings { api_entry_point: Some(fixture.to_string_lossy().into_owned()), ..Default::default() },
+        );
+        let mut harness = Harness::new(App::with_store(store, ledger, Keymap::default()), 100, 16);
+        harness.keys(&[key(KeyCode::Char('3'))]).type_text("say 42").keys(&[key(KeyCode::Enter)]);
+        for _ in 0..200 {
+            harness.subject.tick();
+            if harness.render().contains("2+2 tokens") {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        harness.assert_snapshot("app_chat_reply");
+    }
+
+    #[test]
+    fn routes_bracketed_paste_to_focused_input() {
+        let (store, ledger) = snapshot::store("app-paste");
+        let mut harness = Harness::new(App::with_store(store, ledger, Keymap::default()), 100, 20);
+        harness.keys(&[key(KeyCode::Char('3'))]);
+        harness.subject.handle_paste("first line\nsecond line");
+        harness.assert_snapshot("app_chat_paste");
+        assert!(harness.subject.notifications.latest().is_none());
+
+        harness.keys(&[key(KeyCode::Esc), ctrl('p')]);
+        harness.subject.handle_paste("file3");
+        harness.assert_snapshot("app_palette_paste");
+    }
+
+    struct StubView(char);
+
+    impl View for StubView {
+        fn title(&self) -> &'static str {
+            "Stub View"
+        }
+
+        fn shortcut(&self) -> char {
+            self.0
+        }
+
+        fn render(&self, f: &mut Frame, area: Rect, _theme: &Theme) {
+            f.render_widget(Paragraph::new("stub"), area);
+        }
+
+        fn keymap_scope(&self) -> &'static str {
+            "stub"
+        }
+
+        fn handle_input(&mut self, _key: KeyEvent, _binding: Option<&str>, _notifications: &mut Notifications) -> Action {
+            Action::None
+        }
+    }
+
+    #[test]
+    fn registered_views_get_header_and_shortcut() {
+        let mut views = ViewRegistry::new();
+        views.register(Box::new(StubView('x')));
+        views.register(Box::new(StubView('z')));
+        let mut harness = Harness::new(App::with_views(views, Keymap::default()), 60, 5);
+        harness.keys(&[key(KeyCode::Char('z'))]).assert_snapshot("app_registered_views");
+    }
+
+    #[test]
+    fn switches_themes_from_commands_and_keys() {
+        let (store, ledger) = snapshot::store("app-theme");
+        let mut app = App::with_store(store, ledger, Keymap::default());
+        for c in ":theme light".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+        app.handle_key(key(KeyCode::Enter));
+        assert_eq!(app.themes.active().name, "light");
+        app.handle_key(key(KeyCode::F(2)));
+        assert_eq!(app.themes.active().name, "high-contrast");
+        assert_eq!(app.notifications.latest().unwrap().message, "Theme: high-contrast");
+        for c in ":theme neon".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+        app.handle_key(key(KeyCode::Enter));
+        assert_eq!(app.notifications.latest().unwrap().message, "Unknown theme neon");
+        assert_eq!(app.themes.active(), &Theme::high_contrast());
+    }
+
+    #[test]
+    fn stacks_notifications_over_the_status_bar() {
+        let (store, ledger) = snapshot::store("app-notify");
+        let mut harness = Harness::new(App::with_store(store, ledger, Keymap::default()), 100, 16);
+        harness
+            .type_text(":theme neon")
+            .assert_snapshot("app_command_input")
+            .keys(&[key(KeyCode::Enter), key(KeyCode::Char('2')), key(KeyCode::Char('a'))])
+            .assert_snapshot("app_notifications")
+            .keys(&[key(KeyCode::Esc), key(KeyCode::Esc), ctrl('d')]);
+        assert_eq!(harness.subject.notifications.count(), 1);
+        assert_eq!(harness.subject.notifications.latest().unwrap().message, "Unknown theme neon");
+        harness.subject.notifications.expire(Instant::now() + Duration::from_secs(60));
+        harness.assert_snapshot("app_notifications_cleared");
+    }
+
+    #[test]
+    fn quits_on_escape() {
+        let (store, ledger) = snapshot::store("app-quit");
+        let mut app = App::with_store(store, ledger, Keymap::default());
+        assert!(app.handle_key(key(KeyCode::Char('2'))));
+        assert!(!app.handle_key(key(KeyCode::Esc)));
+    }
+
+    #[test]
+    fn ctrl_z_requests_suspend_even_while_typing() {
+        let (store, ledger) = snapshot::store("app-suspend");
+        let mut app = App::with_store(store, ledger, Keymap::default());
+        app.handle_key(key(KeyCode::Char('3')));
+        assert!(app.handle_key(ctrl('z')));
+        assert!(app.suspend_requested);
+        assert!(!app.handle_key(ctrl('c')));
+    }
+}
@@ -0,0 +1,206 @@
+use crate::http::{HttpRequest, HttpResponse};
+use crate::logging::{header_secrets, is_sensitive, REDACTED};
+use crate::provider::{ErrorKind, ProviderError};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+
+static ACTIVE: OnceLock<Cassette> = OnceLock::new();
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedError {
+    pub kind: ErrorKind,
+    pub status: Option<u16>,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Exchange {
+    pub method: String,
+    pub url: String,
+    pub request_headers: Vec<(String, String)>,
+    pub request_body: Option<String>,
+    pub response: Option<HttpResponse>,
+    pub error: Option<RecordedError>,
+}
+
+enum Mode {
+    Record(PathBuf),
+    Replay(Mutex<Vec<(Exchange, bool)>>),
+}
+
+pub struct Cassette {
+    mode: Mode,
+}
+
+pub fn active() -> Option<&'static Cassette> {
+    ACTIVE.get()
+}
+
+pub fn start_from_args(args: &[String]) -> io::Result<()> {
+    let value = |flag: &str| args.iter().position(|arg| arg == flag).and_then(|index| args.get(index + 1));
+    let cassette = match (value("--record"), value("--replay")) {
+        (Some(_), Some(_)) => {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "--record and --replay are mutually exclusive"));
+        }
+        (Some(path), None) => Cassette::create(Path::new(path))?,
+        (None, Some(path)) => Cassette::open(Path::new(path))?,
+        (None, None) => return Ok(()),
+    };
+    let _ = ACTIVE.set(cassette);
+    Ok(())
+}
+
+impl Cassette {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        File::create(path)?;
+        Ok(Self { mode: Mode::Record(path.to_path_buf()) })
+    }
+
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let exchanges = fs::read_to_string(path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map(|exchange| (exchange, false)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("invalid cassette {}: {}", path.display(), e)))?;
+        Ok(Self { mode: Mode::Replay(Mutex::new(exchanges)) })
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        matches!(self.mode, Mode::Replay(_))
+    }
+
+    pub fn record(&self, request: &HttpRequest, result: &Result<HttpResponse, ProviderError>) {
+        let Mode::Record(path) = &self.mode else {
+            return;
+        };
+        let exchange = redact(request, result);
+        let Ok(line) = serde_json::to_string(&exchange) else {
+            return;
+        };
+        if let Ok(mut file) = OpenOptions::new().append(true).open(path) {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+
+    pub fn replay(&self, request: &HttpRequest) -> Result<HttpResponse, ProviderError> {
+        let Mode::Replay(exchanges) = &self.mode else {
+            return Err(ProviderError::new(ErrorKind::Other, None, "cassette is not replaying".to_string()));
+        };
+        let wanted = redact(request, &Err(ProviderError::new(ErrorKind::Other, None, String::new())));
+        let mut exchanges = exchanges.lock().unwrap();
+        let Some((exchange, used)) = exchanges.iter_mut().find(|(exchange, used)| {
+            !*used
+                && exchange.method == wanted.method
+                && exchange.url == wanted.url
+                && exchange.request_body == wanted.request_body
+        }) else {
+            return Err(ProviderError::new(
+                ErrorKind::Other,
+                None,
+                format!("no recorded exchange left for {} {}", wanted.method, wanted.url),
+            ));
+        };
+        *used = true;
+        match (&exchange.response, &exchange.error) {
+            (Some(response), _) => Ok(response.clone()),
+            (None, Some(error)) => Err(ProviderError::new(error.kind, error.status, error.message.clone())),
+            (None, None) => Err(ProviderError::new(ErrorKind::Other, None, "empty recorded exchange".to_string())),
+        }
+    }
+}
+
+fn redact(request: &HttpRequest, result: &Result<HttpResponse, ProviderError>) -> Exchange {
+    let secrets = header_secrets(&request.headers);
+    let scrub = |text: &str| secrets.iter().fold(text.to_string(), |text, secret| text.replace(secret.as_str(), REDACTED));
+    let headers = |headers: &[(String, String)]| {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if is_sensitive(name) { REDACTED.to_string() } else { scrub(value) };
+                (name.clone(), value)
+            })
+            .collect()
+    };
+
+    let (response, error) = match result {
+        Ok(response) => (
+            Some(HttpResponse { status: response.status, headers: headers(&response.headers), body: scrub(&response.body) }),
+            None,
+        ),
+        Err(error) => (None, Some(RecordedError { kind: error.kind, status: error.status, message: scrub(&error.message) })),
+    };
+    Exchange {
+        method: request.method.to_string(),
+        url: scrub(&request.url),
+        request_headers: headers(&request.headers),
+        request_body: request.body.as_deref().map(scrub),
+        response,
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cassette, REDACTED};
+    use crate::http::{HttpRequest, HttpResponse};
+    use crate::provider::{ErrorKind, ProviderError};
+    use crate::snapshot;
+    use serde_json::json;
+    use std::fs;
+
+    const KEY: &str = "sk-test-0123456789";
+
+    fn request(prompt: &str) -> HttpRequest {
+        HttpRequest::post(format!("https://api.example.com/v1/chat?key={}", KEY))
+            .header("Authorization", format!("Bearer {}", KEY))
+            .header("X-Request-Source", "tests")
+            .json(&json!({ "prompt": prompt }))
+    }
+
+    #[test]
+    fn replays_recorded_exchanges_without_secrets() {
+        let path = snapshot::temp_dir("cassette-replay").join("nested").join("session.jsonl");
+        let recorder = Cassette::create(&path).unwrap();
+        assert!(!recorder.is_replaying());
+        let response = HttpResponse {
+            status: 200,
+            headers: vec![("Set-Cookie".to_string(), "session=abc".to_string()), ("X-Echo".to_string(), KEY.to_string())],
+            body: format!("{{\"answer\":\"hi\",\"echo\":\"{}\"}}", KEY),
+        };
+        recorder.record(&request("hello"), &Ok(response));
+        recorder.record(&request("again"), &Err(ProviderError::new(ErrorKind::Quota, Some(429), format!("quota for {}", KEY))));
+
+        let recorded = fs::read_to_string(&path).unwrap();
+        assert!(!recorded.contains(KEY), "{}", recorded);
+        assert!(!recorded.contains("session=abc"), "{}", recorded);
+        assert!(recorded.contains("X-Request-Source\",\"tests"), "{}", recorded);
+
+        let player = Cassette::open(&path).unwrap();
+        assert!(player.is_replaying());
+        let error = player.replay(&request("again")).unwrap_err();
+        assert_eq!((error.kind, error.status, error.message), (ErrorKind::Quota, Some(429), format!("quota for {}", REDACTED)));
+        let replayed = player.replay(&request("hello")).unwrap();
+        assert_eq!(replayed.status, 200);
+        assert_eq!(replayed.header("set-cookie"), Some(REDACTED));
+        assert_eq!(replayed.body, format!("{{\"answer\":\"hi\",\"echo\":\"{}\"}}", REDACTED));
+
+        let error = player.replay(&request("hello")).unwrap_err();
+        assert!(error.message.starts_with("no recorded exchange left for POST https://api.example.com/v1/chat?key=[REDACTED]"));
+    }
+
+    #[test]
+    fn rejects_invalid_cassettes() {
+        let path = snapshot::temp_dir("cassette-invalid").join("session.jsonl");
+        fs::write(&path, "{\"method\":\"GET\"}\n").unwrap();
+        let error = Cassette::open(&path).err().unwrap();
+        assert!(error.to_string().starts_with("invalid cassette"), "{}", error);
+    }
+}
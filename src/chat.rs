@@ -0,0 +1,429 @@
+use crate::http::{self, HttpRequest};
+use crate::logging;
+use crate::mock;
+use crate::provider::{ErrorKind, ProviderError, ProviderInstance, ProviderType, ResolvedSettings};
+use crate::retry::{self, RateLimiter, RetryEvent};
+use crate::routing::Route;
+use crate::usage::Usage;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::System => "system",
+            Role::User => "user",
+            Role::Assistant => "assistant",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: Role,
+    pub content: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+#[derive(Clone, Debug)]
+pub struct ChatResponse {
+    pub content: String,
+    pub model: String,
+    pub usage: Usage,
+    pub tool_calls: Vec<ToolCall>,
+}
+
+pub struct Candidate {
+    pub name: String,
+    pub resolved: Result<(ProviderInstance, ResolvedSettings), String>,
+    pub limiter: RateLimiter,
+}
+
+pub struct Dispatch {
+    pub target: String,
+    pub route: Option<Route>,
+    pub candidates: Vec<Candidate>,
+}
+
+pub struct ChatRequest {
+    pub dispatch: Dispatch,
+    pub messages: Vec<ChatMessage>,
+}
+
+pub enum ChatEvent {
+    Retry { provider: String, event: RetryEvent },
+    Fallback { provider: String, error: ProviderError },
+    Skipped { provider: String, reason: String },
+    Chunk(String),
+}
+
+impl ChatEvent {
+    pub fn note(&self) -> Option<String> {
+        match self {
+            ChatEvent::Retry { provider, event } => Some(format!(
+                "{} retry #{} {} after {:.1}s",
+                provider,
+                event.attempt,
+                event.error.kind.label(),
+                event.delay.as_secs_f64()
+            )),
+            ChatEvent::Fallback { provider, error } => Some(format!("{} failed ({}), falling back", provider, error.kind.label())),
+            ChatEvent::Skipped { provider, reason } => Some(format!("skipped {}: {}", provider, reason)),
+            ChatEvent::Chunk(_) => None,
+        }
+    }
+}
+
+pub fn dispatch(
+    dispatch: &Dispatch,
+    messages: &[ChatMessage],
+    on_event: &mut dyn FnMut(ChatEvent),
+) -> Result<(String, ChatResponse), String> {
+    let on_event = &mut |event: ChatEvent| {
+        if let Some(note) = event.note() {
+            logging::warn("chat", &note, vec![("target", json!(dispatch.target))]);
+        }
+        on_event(event);
+    };
+    let candidates: Vec<&str> = dispatch.candidates.iter().map(|candidate| candidate.name.as_str()).collect();
+    logging::debug("chat", "dispatching", vec![("target", json!(dispatch.target)), ("candidates", json!(candidates))]);
+    for (index, candidate) in dispatch.candidates.iter().enumerate() {
+        let (instance, settings) = match &candidate.resolved {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                on_event(ChatEvent::Skipped { provider: candidate.name.clone(), reason: e.clone() });
+                continue;
+            }
+        };
+        logging::add_secrets(std::slice::from_ref(&settings.api_key.value));
+        let started = Instant::now();
+        let fallback = dispatch.route.as_ref().filter(|_| index + 1 < dispatch.candidates.len());
+        match complete(candidate, &instance.provider_type, settings, messages, fallback, on_event) {
+            Ok(response) => {
+                logging::info(
+                    "chat",
+                    "completion finished",
+                    vec![
+                        ("target", json!(dispatch.target)),
+                        ("provider", json!(candidate.name)),
+                        ("model", json!(response.model)),
+                        ("usage", json!(response.usage)),
+                        ("duration_ms", json!(started.elapsed().as_millis() as u64)),
+                    ],
+                );
+                return Ok((candidate.name.clone(), response));
+            }
+            Err(e) if fallback.is_some_and(|route| route.should_fall_back(&e)) => {
+                on_event(ChatEvent::Fallback { provider: candidate.name.clone(), error: e });
+            }
+            Err(e) => {
+                let message = format!("{} failed: {}", candidate.name, e);
+                logging::error("chat", &message, vec![("target", json!(dispatch.target)), ("kind", json!(e.kind))]);
+                return Err(message);
+            }
+        }
+    }
+    let message = format!("no usable provider for {}", dispatch.target);
+    logging::error("chat", &message, Vec::new());
+    Err(message)
+}
+
+fn complete(
+    candidate: &Candidate,
+    provider_type: &ProviderType,
+    settings: &ResolvedSettings,
+    messages: &[ChatMessage],
+    fallback: Option<&Route>,
+    on_event: &mut dyn FnMut(ChatEvent),
+) -> Result<ChatResponse, ProviderError> {
+    let on_event = RefCell::new(on_event);
+    retry::with_retry(
+        &settings.retry,
+        &candidate.limiter,
+        // Errors the route falls back on go straight to the next target instead of being retried here.
+        |error| retry::is_retryable(error) && !fallback.is_some_and(|route| route.should_fall_back(error)),
+        |event| (on_event.borrow_mut())(ChatEvent::Retry { provider: candidate.name.clone(), event: event.clone() }),
+        || send(provider_type, settings, messages, &mut |chunk| (on_event.borrow_mut())(ChatEvent::Chunk(chunk.to_string()))),
+    )
+}
+
+fn send(
+    provider_type: &ProviderType,
+    settings: &ResolvedSettings,
+    messages: &[ChatMessage],
+    on_chunk: &mut dyn FnMut(&str),
+) -> Result<ChatResponse, ProviderError> {
+    let base = settings.entry_point();
+    let key = settings.api_key.value.as_str();
+    let model = settings.model.as_str();
+
+    let (request, body) = match provider_type {
+        ProviderType::OpenAI | ProviderType::Grog => (
+            HttpRequest::post(format!("{}/chat/completions", base))
+                .header("Authorization", format!("Bearer {}", key)),
+            json!({ "model": model, "messages": openai_messages(messages) }),
+        ),
+        ProviderType::OpenAICompatible => (
+            settings.auth.apply(HttpRequest::post(format!("{}/chat/completions", base)), key),
+            json!({ "model": model, "messages": openai_messages(messages) }),
+        ),
+        ProviderType::AzureOpenAI => (
+            HttpRequest::post(format!(
+                "{}/openai/deployments/{}/chat/completions?api-version=2024-02-01",
+                base, settings.api_deployment.value
+            ))
+            .header("api-key", key),
+            json!({ "messages": openai_messages(messages) }),
+        ),
+        ProviderType::Ollama => (
+            HttpRequest::post(format!("{}/api/chat", base)),
+            json!({ "model": model, "messages": openai_messages(messages), "stream": false }),
+        ),
+        ProviderType::Gemini => {
+            let contents: Vec<Value> = messages
+                .iter()
+                .filter(|message| message.role != Role::System)
+                .map(|message| {
+                    let role = if message.role == Role::Assistant { "model" } else { "user" };
+                    json!({ "role": role, "parts": [{ "text": message.content }] })
+                })
+                .collect();
+            let mut body = json!({ "contents": contents });
+            if let Some(system) = system_prompt(messages) {
+                body["systemInstruction"] = json!({ "parts": [{ "text": system }] });
+            }
+            (
+                HttpRequest::post(format!("{}/models/{}:generateContent", base, model))
+                    .header("x-goog-api-key", key),
+                body,
+            )
+        }
+        ProviderType::Claude => {
+            let chat: Vec<Value> = messages
+                .iter()
+                .filter(|message| message.role != Role::System)
+                .map(|message| json!({ "role": message.role.as_str(), "content": message.content }))
+                .collect();
+            let mut body = json!({ "model": model, "max_tokens": 4096, "messages": chat });
+            if let Some(system) = system_prompt(messages) {
+                body["system"] = json!(system);
+            }
+            (
+                HttpRequest::post(format!("{}/messages", base))
+                    .header("x-api-key", key)
+                    .header("anthropic-version", "2023-06-01"),
+                body,
+            )
+        }
+        ProviderType::Mock => {
+            return mock::send(Path::new(&settings.api_entry_point.value), model, messages, on_chunk);
+        }
+    };
+
+    let response = http::execute(request.json(&body), &settings.network)?;
+    let status = response.status;
+    let retry_after = response.header("retry-after").and_then(parse_retry_after);
+    let text = response.body;
+    if let Some(kind) = ErrorKind::from_status(status) {
+        return Err(ProviderError { retry_after, ..ProviderError::new(kind, Some(status), error_message(&text)) });
+    }
+    let value: Value = serde_json::from_str(&text)
+        .map_err(|e| ProviderError::new(ErrorKind::Other, Some(status), e.to_string()))?;
+
+    Ok(parse_response(provider_type, model, &value))
+}
+
+fn openai_messages(messages: &[ChatMessage]) -> Vec<Value> {
+    messages
+        .iter()
+        .map(|message| json!({ "role": message.role.as_str(), "content": message.content }))
+        .collect()
+}
+
+fn system_prompt(messages: &[ChatMessage]) -> Option<String> {
+    let system: Vec<&str> = messages
+        .iter()
+        .filter(|message| message.role == Role::System)
+        .map(|message| message.content.as_str())
+        .collect();
+    (!system.is_empty()).then(|| system.join("\n\n"))
+}
+
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(seconds) = value.trim().parse::<f64>() {
+        return Some(Duration::from_secs_f64(seconds.max(0.0)));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().ok()
+}
+
+fn error_message(text: &str) -> String {
+    serde_json::from_str::<Value>(text)
+        .ok()
+        .and_then(|value| {
+            value["error"]["message"]
+                .as_str()
+                .or_else(|| value["error"].as_str())
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| text.lines().next().unwrap_or_default().to_string())
+}
+
+fn parse_response(provider_type: &ProviderType, model: &str, value: &Value) -> ChatResponse {
+    let tokens = |pointer: &str| value.pointer(pointer).and_then(Value::as_u64).unwrap_or_default();
+    let (content, usage) = match provider_type {
+        ProviderType::OpenAI | ProviderType::Grog | ProviderType::AzureOpenAI | ProviderType::OpenAICompatible | ProviderType::Mock => (
+            value["choices"][0]["message"]["content"].as_str().unwrap_or_default().to_string(),
+            Usage { prompt_tokens: tokens("/usage/prompt_tokens"), completion_tokens: tokens("/usage/completion_tokens") },
+        ),
+        ProviderType::Ollama => (
+            value["message"]["content"].as_str().unwrap_or_default().to_string(),
+            Usage { prompt_tokens: tokens("/prompt_eval_count"), completion_tokens: tokens("/eval_count") },
+        ),
+        ProviderType::Gemini => (
+            value["candidates"][0]["content"]["parts"]
+                .as_array()
+                .map(|parts| parts.iter().filter_map(|part| part["text"].as_str()).collect::<String>())
+                .unwrap_or_default(),
+            Usage {
+                prompt_tokens: tokens("/usageMetadata/promptTokenCount"),
+                completion_tokens: tokens("/usageMetadata/candidatesTokenCount"),
+            },
+        ),
+        ProviderType::Claude => (
+            value["content"]
+                .as_array()
+                .map(|blocks| blocks.iter().filter_map(|block| block["text"].as_str()).collect::<String>())
+                .unwrap_or_default(),
+            Usage { prompt_tokens: tokens("/usage/input_tokens"), completion_tokens: tokens("/usage/output_tokens") },
+        ),
+    };
+    let model = value["model"]
+        .as_str()
+        .or_else(|| value["modelVersion"].as_str())
+        .unwrap_or(model)
+        .to_string();
+
+    ChatResponse { content, model, usage, tool_calls: parse_tool_calls(provider_type, value) }
+}
+
+fn parse_tool_calls(provider_type: &ProviderType, value: &Value) -> Vec<ToolCall> {
+    let calls = match provider_type {
+        ProviderType::Ollama => &value["message"]["tool_calls"],
+        ProviderType::Gemini => &value["candidates"][0]["content"]["parts"],
+        ProviderType::Claude => &value["content"],
+        _ => &value["choices"][0]["message"]["tool_calls"],
+    };
+    calls
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|call| {
+            let (name, arguments) = match provider_type {
+                ProviderType::Gemini => (&call["functionCall"]["name"], call["functionCall"]["args"].clone()),
+                ProviderType::Claude if call["type"] == "tool_use" => (&call["name"], call["input"].clone()),
+                ProviderType::Claude => return None,
+                _ => {
+                    let arguments = &call["function"]["arguments"];
+                    let arguments = match arguments.as_str() {
+                        Some(text) => serde_json::from_str(text).unwrap_or_else(|_| json!(text)),
+                        None => arguments.clone(),
+                    };
+                    (&call["function"]["name"], arguments)
+                }
+            };
+            Some(ToolCall { name: name.as_str()?.to_string(), arguments })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dispatch, error_message, parse_response, system_prompt, ChatEvent, ChatMessage, Role};
+    use crate::provider::{ProviderInstance, ProviderSettings, ProviderType};
+    use crate::routing::{FallbackOn, Route, RouteTarget, Strategy};
+    use crate::snapshot;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn parses_responses_of_each_provider() {
+        let openai = json!({ "model": "gpt-4o-2024", "choices": [{ "message": { "content": "Hi" } }] });
+        let ollama = json!({ "message": { "content": "Hi" } });
+        let gemini = json!({ "modelVersion": "gemini-1.5", "candidates": [{ "content": { "parts": [{ "text": "H" }, { "text": "i" }] } }] });
+        let claude = json!({ "model": "claude-3", "content": [{ "type": "text", "text": "Hi" }] });
+
+        let parsed = |provider_type: ProviderType, value| {
+            let response = parse_response(&provider_type, "configured", &value);
+            (response.content, response.model)
+        };
+        assert_eq!(parsed(ProviderType::OpenAI, openai), ("Hi".to_string(), "gpt-4o-2024".to_string()));
+        assert_eq!(parsed(ProviderType::Ollama, ollama), ("Hi".to_string(), "configured".to_string()));
+        assert_eq!(parsed(ProviderType::Gemini, gemini), ("Hi".to_string(), "gemini-1.5".to_string()));
+        assert_eq!(parsed(ProviderType::Claude, claude), ("Hi".to_string(), "claude-3".to_string()));
+    }
+
+    #[test]
+    fn extracts_error_messages_and_system_prompts() {
+        assert_eq!(error_message(r#"{"error":{"message":"invalid api key"}}"#), "invalid api key");
+        assert_eq!(error_message(r#"{"error":"model not found"}"#), "model not found");
+        assert_eq!(error_message("Bad Gateway\n<html>"), "Bad Gateway");
+
+        let message = |role, content: &str| ChatMessage { role, content: content.to_string() };
+        let messages = [message(Role::System, "Be brief."), message(Role::User, "Hi"), message(Role::System, "Use English.")];
+        assert_eq!(system_prompt(&messages).as_deref(), Some("Be brief.\n\nUse English."));
+        assert_eq!(system_prompt(&messages[1..2]), None);
+    }
+
+    #[test]
+    fn falls_back_without_retrying_when_targets_remain() {
+        let dir = snapshot::temp_dir("chat-fallback");
+        let (store, _) = snapshot::store("chat-fallback-store");
+        let mut store = store.borrow_mut();
+        for (name, fixture) in [
+            ("limited", "responses:\n  - error: { status: 429, message: slow down }\n"),
+            ("backup", "responses:\n  - content: from backup\n"),
+        ] {
+            let path = dir.join(format!("{}.yaml", name));
+            fs::write(&path, fixture).unwrap();
+            store.update(
+                ProviderInstance { name: name.to_string(), provider_type: ProviderType::Mock },
+                ProviderSettings { api_entry_point: Some(path.display().to_string()), ..ProviderSettings::default() },
+            );
+        }
+        store.routes.insert(
+            "default".to_string(),
+            Route {
+                name: "default".to_string(),
+                strategy: Strategy::Priority,
+                targets: ["limited", "backup"].map(|provider| RouteTarget { provider: provider.to_string(), weight: 1 }).to_vec(),
+                fallback_on: vec![FallbackOn::RateLimit],
+                rules: Vec::new(),
+            },
+        );
+
+        let messages = [ChatMessage { role: Role::User, content: "hello".to_string() }];
+        let mut events = Vec::new();
+        let (provider, response) =
+            dispatch(&store.prepare("route:default", "hello", None), &messages, &mut |event| events.push(event)).unwrap();
+        assert_eq!((provider.as_str(), response.content.as_str()), ("backup", "from backup"));
+        assert!(matches!(events.as_slice(), [ChatEvent::Fallback { provider, .. }] if provider == "limited"));
+    }
+}
@@ -0,0 +1,631 @@
+use crate::chat::{ChatEvent, ChatMessage, ChatRequest, ChatResponse, Role, ToolCall};
+use crate::editor::EditTarget;
+use crate::keymap::Keymap;
+use crate::store::SharedStore;
+use crate::notify::{Notifications, Severity};
+use crate::theme::Theme;
+use crate::traits::{Action, PaletteEntry, Status, Update, View};
+use crate::usage::{SharedLedger, Usage};
+use crate::widgets::TextInput;
+use chrono::Local;
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::Modifier,
+    text::{Line, Span},
+    widgets::{Paragraph, Wrap},
+    Frame,
+};
+
+struct ChatEntry {
+    message: ChatMessage,
+    provider: Option<String>,
+    model: Option<String>,
+    usage: Option<Usage>,
+    cost: f64,
+    tool_calls: Vec<ToolCall>,
+}
+
+struct Conversation {
+    id: String,
+    entries: Vec<ChatEntry>,
+}
+
+impl ChatEntry {
+    fn new(role: Role, content: String) -> Self {
+        Self {
+            message: ChatMessage { role, content },
+            provider: None,
+            model: None,
+            usage: None,
+            cost: 0.0,
+            tool_calls: Vec::new(),
+        }
+    }
+}
+
+struct PendingRequest {
+    conversation: usize,
+    streaming: bool,
+}
+
+impl Conversation {
+    fn new(index: usize) -> Self {
+        Self {
+            id: format!("{}-{}", Local::now().format("%Y%m%d%H%M%S"), index),
+            entries: Vec::new(),
+        }
+    }
+
+    fn cost(&self) -> f64 {
+        self.entries.iter().fold(0.0, |total, entry| total + entry.cost)
+    }
+}
+
+pub struct ChatView {
+    store: SharedStore,
+    ledger: SharedLedger,
+    conversations: Vec<Conversation>,
+    active_conversation: usize,
+    selected_target: usize,
+    model_override: Option<String>,
+    selected_entry: Option<usize>,
+    input: TextInput,
+    focused: bool,
+    pending: Option<PendingRequest>,
+    prompt_titles: [String; 2],
+    target_hints: [String; 2],
+    conversation_hints: [String; 2],
+}
+
+impl ChatView {
+    pub fn new(store: SharedStore, ledger: SharedLedger) -> Self {
+        let mut view = Self {
+            store,
+            ledger,
+            conversations: vec![Conversation::new(1)],
+            active_conversation: 0,
+            selected_target: 0,
+            model_override: None,
+            selected_entry: None,
+            input: TextInput::multiline(),
+            focused: true,
+            pending: None,
+            prompt_titles: Default::default(),
+            target_hints: Default::default(),
+            conversation_hints: Default::default(),
+        };
+        view.set_keymap(&Keymap::default());
+        view
+    }
+
+    fn target_name(&self) -> Option<String> {
+        let targets = self.store.borrow().targets();
+        targets.get(self.selected_target.min(targets.len().saturating_sub(1))).cloned()
+    }
+
+    fn send(&mut self, notifications: &mut Notifications) -> Action {
+        let prompt = self.input.value().trim().to_string();
+        if prompt.is_empty() {
+            return Action::None;
+        }
+        if self.pending.is_some() {
+            notifications.warning("Waiting for the previous reply");
+            return Action::None;
+        }
+        if let Err(e) = self.ledger.borrow().check_budget() {
+            notifications.error(format!("Request blocked: {}", e));
+            return Action::None;
+        }
+        let Some(target) = self.target_name() else {
+            notifications.warning("No provider configured, add one in the Provider View");
+            return Action::None;
+        };
+        let dispatch = self.store.borrow_mut().prepare(&target, &prompt, self.model_override.as_deref());
+
+        self.input.take();
+        self.selected_entry = None;
+        let conversation = &mut self.conversations[self.active_conversation];
+        conversation.entries.push(ChatEntry::new(Role::User, prompt));
+        let messages: Vec<ChatMessage> = conversation.entries.iter().map(|entry| entry.message.clone()).collect();
+        self.pending = Some(PendingRequest { conversation: self.active_conversation, streaming: false });
+        Action::Send(ChatRequest { dispatch, messages })
+    }
+
+    fn finish(&mut self, outcome: Result<(String, ChatResponse), String>) -> Action {
+        let Some(pending) = self.pending.take() else {
+            return Action::None;
+        };
+        let conversation = &mut self.conversations[pending.conversation];
+        if pending.streaming {
+            conversation.entries.pop();
+        }
+        match outcome {
+            Ok((provider_name, response)) => {
+                let mut action = Action::None;
+                let cost = match self.ledger.borrow_mut().record(&provider_name, &response.model, &conversation.id, response.usage) {
+                    Ok(record) => record.cost,
+                    Err(e) => {
+                        action = Action::Notify(Severity::Warning, format!("Failed to record usage: {}", e));
+                        0.0
+                    }
+                };
+                conversation.entries.push(ChatEntry {
+                    provider: Some(provider_name),
+                    model: Some(response.model),
+                    usage: Some(response.usage),
+                    cost,
+                    tool_calls: response.tool_calls,
+                    ..ChatEntry::new(Role::Assistant, response.content)
+                });
+                action
+            }
+            Err(e) => Action::Notify(Severity::Error, e),
+        }
+    }
+
+    fn selected(&self) -> Option<&ChatEntry> {
+        let entries = &self.conversations[self.active_conversation].entries;
+        entries.get(self.selected_entry.unwrap_or(entries.len().saturating_sub(1)))
+    }
+
+    fn message_lines(&self, theme: &Theme) -> (Vec<Line<'_>>, Option<usize>) {
+        let mut lines = Vec::new();
+        let mut selected_line = None;
+        for (index, entry) in self.conversations[self.active_conversation].entries.iter().enumerate() {
+            let header = match entry.message.role {
+                Role::User => Span::styled("you", theme.highlight),
+                _ => Span::styled(
+                    format!(
+                        "{} [{} {}, {}+{} tokens, ${:.5}]",
+                        entry.message.role.as_str(),
+                        entry.provider.as_deref().unwrap_or_default(),
+                        entry.model.as_deref().unwrap_or_default(),
+                        entry.usage.unwrap_or_default().prompt_tokens,
+                        entry.usage.unwrap_or_default().completion_tokens,
+                        entry.cost,
+                    ),
+                    theme.success.add_modifier(Modifier::BOLD),
+                ),
+            };
+            if self.selected_entry == Some(index) {
+                selected_line = Some(lines.len());
+                lines.push(Line::from(header.patch_style(theme.highlight)));
+            } else {
+                lines.push(Line::from(header));
+            }
+            lines.extend(entry.message.content.lines().map(Line::raw));
+            lines.extend(entry.tool_calls.iter().map(|call| {
+                Line::styled(format!("→ {}({})", call.name, call.arguments), theme.accent)
+            }));
+            lines.push(Line::raw(""));
+        }
+        (lines, selected_line)
+    }
+}
+
+fn code_blocks(content: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Option<Vec<&str>> = None;
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") {
+            match current.take() {
+                Some(lines) => blocks.push(lines.join("\n")),
+                None => current = Some(Vec::new()),
+            }
+        } else if let Some(lines) = &mut current {
+            lines.push(line);
+        }
+    }
+    blocks.extend(current.map(|lines| lines.join("\n")));
+    blocks
+}
+
+impl View for ChatView {
+    fn title(&self) -> &'static str {
+        "Chat View"
+    }
+
+    fn shortcut(&self) -> char {
+        '3'
+    }
+
+    fn hints(&self) -> &'static [(&'static str, &'static str)] {
+        if self.focused {
+            &[("send", "Send"), ("next_target", "Target"), ("next_model", "Model"), ("new_conversation", "New conversation"), ("next_conversation", "Next conversation"), ("edit", "Edit")]
+        } else {
+            &[("focus", "Focus"), ("next_target", "Target"), ("next_model", "Model"), ("new_conversation", "New conversation"), ("copy_message", "Copy"), ("copy_code", "Copy code"), ("edit", "Edit")]
+        }
+    }
+
+    fn set_keymap(&mut self, keymap: &Keymap) {
+        self.prompt_titles = [
+            format!("Prompt {}", keymap.hints("chat", &[("focus", "Focus")])),
+            format!("Prompt {}", keymap.hints("chat_input", &[("send", "Send"), ("newline", "Newline"), ("unfocus", "Leave")])),
+        ];
+        self.target_hints = ["chat", "chat_input"].map(|scope| keymap.hints(scope, &[("next_target", "next"), ("next_model", "model")]));
+        self.conversation_hints = ["chat", "chat_input"]
+            .map(|scope| keymap.hints(scope, &[("new_conversation", "new"), ("prev_conversation", "prev"), ("next_conversation", "next")]));
+    }
+
+    fn render(&self, f: &mut Frame, area: ratatui::layout::Rect, theme: &Theme) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(self.input.lines().clamp(1, 6) + 2),
+            ])
+            .split(area);
+
+        let target = self.target_name();
+        let target_cost = target
+            .as_deref()
+            .map(|name| {
+                let route = self.store.borrow().routes.get(name.strip_prefix("route:").unwrap_or_default()).cloned();
+                self.ledger.borrow().total_where(|record| match &route {
+                    Some(route) => route.targets.iter().any(|target| target.provider == record.provider)
+                        || route.rules.iter().any(|rule| rule.provider == record.provider),
+                    None => record.provider == name,
+                })
+                .cost
+            })
+            .unwrap_or_default();
+        let status_line = Line::from(vec![
+            Span::styled(
+                format!(
+                    " Target: {}{} {} ",
+                    target.as_deref().unwrap_or("none"),
+                    self.model_override.as_deref().map(|model| format!(" ({})", model)).unwrap_or_default(),
+                    self.target_hints[self.focused as usize]
+                ),
+                theme.success,
+            ),
+            Span::styled(
+                format!(
+                    " Conversation {}/{} {} ",
+                    self.active_conversation + 1,
+                    self.conversations.len(),
+                    self.conversation_hints[self.focused as usize]
+                ),
+                theme.success,
+            ),
+            Span::styled(
+                format!(
+                    " Cost: ${:.5} conversation, ${:.5} target ",
+                    self.conversations[self.active_conversation].cost(),
+                    target_cost
+                ),
+                theme.warning,
+            ),
+        ]);
+        f.render_widget(Paragraph::new(status_line), chunks[0]);
+
+        let (lines, selected_line) = self.message_lines(theme);
+        let visible = chunks[1].height.saturating_sub(2) as usize;
+        let bottom = lines.len().saturating_sub(visible);
+        let scroll = selected_line.map_or(bottom, |line| line.min(bottom)) as u16;
+        let messages = Paragraph::new(lines)
+            .block(theme.block("Chat"))
+            .wrap(Wrap { trim: false })
+            .scroll((scroll, 0));
+        f.render_widget(messages, chunks[1]);
+
+        let block = theme.block(self.prompt_titles[self.focused as usize].as_str());
+        let inner = block.inner(chunks[2]);
+        f.render_widget(block, chunks[2]);
+        self.input.render(f, inner, if self.focused { theme.text } else { theme.muted }, self.focused);
+    }
+
+    fn status(&self) -> Status {
+        let entries = &self.conversations[self.active_conversation].entries;
+        let last = entries.iter().rev().find(|entry| entry.usage.is_some());
+        Status {
+            provider: self.target_name(),
+            model: self.model_override.clone().or_else(|| last.and_then(|entry| entry.model.clone())),
+            context_tokens: last.and_then(|entry| entry.usage).map(|usage| usage.prompt_tokens + usage.completion_tokens),
+            busy: self.pending.is_some(),
+        }
+    }
+
+    fn wants_raw_input(&self) -> bool {
+        self.focused
+    }
+
+    fn handle_paste(&mut self, text: &str) {
+        self.focused = true;
+        self.input.insert_str(text);
+    }
+
+    fn on_enter(&mut self) {
+        self.focused = true;
+    }
+
+    fn update(&mut self, update: Update) -> Action {
+        match update {
+            Update::Chat(ChatEvent::Chunk(chunk)) => {
+                let Some(pending) = &mut self.pending else {
+                    return Action::None;
+                };
+                let entries = &mut self.conversations[pending.conversation].entries;
+                if !pending.streaming {
+                    pending.streaming = true;
+                    entries.push(ChatEntry::new(Role::Assistant, String::new()));
+                }
+                if let Some(entry) = entries.last_mut() {
+                    entry.message.content.push_str(&chunk);
+                }
+                Action::None
+            }
+            Update::Chat(event) => match (&self.pending, event.note()) {
+                (Some(_), Some(note)) => Action::Notify(Severity::Warning, note),
+                _ => Action::None,
+            },
+            Update::Finished(outcome) => self.finish(outcome),
+            Update::Edited(Ok(text)) => {
+                self.input.set_value(text.trim_end_matches('\n'));
+                self.focused = true;
+                Action::None
+            }
+            Update::Edited(Err(e)) => Action::Notify(Severity::Error, format!("Edit failed: {}", e)),
+        }
+    }
+
+    fn keymap_scope(&self) -> &'static str {
+        if self.focused {
+            "chat_input"
+        } else {
+            "chat"
+        }
+    }
+
+    fn palette_entries(&self) -> Vec<PaletteEntry> {
+        let conversations = self.conversations.iter().enumerate().map(|(index, conversation)| {
+            let first = conversation.entries.first().map(|entry| entry.message.content.lines().next().unwrap_or_default());
+            PaletteEntry {
+                label: format!("Conversation {}{}", index + 1, first.map(|first| format!(": {}", first)).unwrap_or_default()),
+                command: format!("conversation:{}", index),
+            }
+        });
+        let targets = self.store.borrow().targets().into_iter().enumerate().map(|(index, target)| PaletteEntry {
+            label: format!("Chat with {}", target),
+            command: format!("target:{}", index),
+        });
+        conversations.chain(targets).collect()
+    }
+
+    fn run_command(&mut self, command: &str, _notifications: &mut Notifications) -> Action {
+        let Some((kind, index)) = command.split_once(':').and_then(|(kind, index)| Some((kind, index.parse::<usize>().ok()?))) else {
+            return Action::None;
+        };
+        match kind {
+            "conversation" if index < self.conversations.len() => {
+                self.active_conversation = index;
+                self.selected_entry = None;
+            }
+            "target" if index < self.store.borrow().targets().len() => {
+                self.selected_target = index;
+                self.model_override = None;
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn handle_input(&mut self, key: KeyEvent, binding: Option<&str>, notifications: &mut Notifications) -> Action {
+        match (binding, key.code) {
+            (Some("focus"), _) => {
+                self.focused = true;
+            }
+            (Some("unfocus"), _) => {
+                self.focused = false;
+            }
+            (Some("send"), _) => {
+                return self.send(notifications);
+            }
+            (Some("newline"), _) => {
+                self.input.insert_str("\n");
+            }
+            (Some("next_target"), _) => {
+                let targets = self.store.borrow().targets().len();
+                if targets > 0 {
+                    self.selected_target = (self.selected_target + 1) % targets;
+                    self.model_override = None;
+                }
+            }
+            (Some("next_model"), _) => {
+                let models = self
+                    .target_name()
+                    .and_then(|target| self.store.borrow().resolve(&target).ok())
+                    .map(|(_, settings)| settings.models)
+                    .unwrap_or_default();
+                self.model_override = match &self.model_override {
+                    None => models.first().cloned(),
+                    Some(current) => models.iter().skip_while(|model| *model != current).nth(1).cloned(),
+                };
+            }
+            (Some("new_conversation"), _) => {
+                self.conversations.push(Conversation::new(self.conversations.len() + 1));
+                self.active_conversation = self.conversations.len() - 1;
+                self.selected_entry = None;
+            }
+            (Some("prev_conversation"), _) if self.active_conversation > 0 => {
+                self.active_conversation -= 1;
+                self.selected_entry = None;
+            }
+            (Some("next_conversation"), _) if self.active_conversation + 1 < self.conversations.len() => {
+                self.active_conversation += 1;
+                self.selected_entry = None;
+            }
+            (Some("select_prev"), _) => {
+                let count = self.conversations[self.active_conversation].entries.len();
+                if count > 0 {
+                    self.selected_entry = Some(self.selected_entry.unwrap_or(count - 1).saturating_sub(1));
+                }
+            }
+            (Some("select_next"), _) => {
+                let count = self.conversations[self.active_conversation].entries.len();
+                self.selected_entry = self.selected_entry.map(|index| (index + 1).min(count.saturating_sub(1)));
+            }
+            (Some("edit"), _) => {
+                return Action::Edit(EditTarget::Text(self.input.value().to_string()));
+            }
+            (Some("copy_message"), _) => {
+                return match self.selected() {
+                    Some(entry) => Action::Copy(entry.message.content.clone()),
+                    None => Action::Notify(Severity::Warning, "No message to copy".to_string()),
+                };
+            }
+            (Some("copy_code"), _) => {
+                let blocks = self.selected().map(|entry| code_blocks(&entry.message.content)).unwrap_or_default();
+                if blocks.is_empty() {
+                    return Action::Notify(Severity::Warning, "No code block in the selected message".to_string());
+                }
+                return Action::Copy(blocks.join("\n"));
+            }
+            _ if self.focused => {
+                self.input.handle_input(key, binding);
+            }
+            _ => {}
+        }
+        Action::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChatEntry, ChatView};
+    use crate::chat::{self, Role};
+    use crate::editor::EditTarget;
+    use crate::env::Env;
+    use crate::keymap::Keymap;
+    use crate::notify::Notifications;
+    use crate::provider::{ProviderInstance, ProviderSettings, ProviderType};
+    use crate::retry::RetryPolicy;
+    use crate::snapshot::{self, key};
+    use crate::store::ProviderStore;
+    use crate::traits::{Action, Update, View};
+    use crate::usage::UsageLedger;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    fn view(name: &str) -> ChatView {
+        let dir = snapshot::temp_dir(name);
+        let store = ProviderStore::load(dir.join("providers.yaml"), dir.join("vault.bin"), Env::default());
+        ChatView::new(store.shared(), UsageLedger::load(&dir.join("usage.yaml"), dir.join("usage.jsonl")).shared())
+    }
+
+    fn type_and_send(view: &mut ChatView, text: &str) -> Vec<String> {
+        let mut notifications = Notifications::default();
+        for c in text.chars() {
+            view.handle_input(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE), None, &mut notifications);
+        }
+        let mut actions = vec![view.handle_input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE), Some("send"), &mut notifications)];
+        if let Action::Send(request) = &actions[0] {
+            let mut events = Vec::new();
+            let outcome = chat::dispatch(&request.dispatch, &request.messages, &mut |event| events.push(event));
+            actions = events.into_iter().map(|event| view.update(Update::Chat(event))).collect();
+            actions.push(view.update(Update::Finished(outcome)));
+        }
+        let notified = actions.into_iter().filter_map(|action| match action {
+            Action::Notify(_, message) => Some(message),
+            _ => None,
+        });
+        notifications.latest().map(|notification| notification.message.clone()).into_iter().chain(notified).collect()
+    }
+
+    #[test]
+    fn reports_providers_that_cannot_be_used() {
+        let mut view = view("chat-view-no-provider");
+        assert_eq!(type_and_send(&mut view, "hello"), ["No provider configured, add one in the Provider View"]);
+        assert_eq!(view.input.value(), "hello");
+
+        let mut store = view.store.borrow_mut();
+        store.instances.insert("Work".to_string(), ProviderInstance { name: "Work".to_string(), provider_type: ProviderType::OpenAI });
+        store.settings.insert("Work".to_string(), ProviderSettings { api_key: Some("vault:Work".to_string()), ..ProviderSettings::default() });
+        drop(store);
+        assert_eq!(type_and_send(&mut view, "!"), ["skipped Work: vault is locked", "no usable provider for Work"]);
+        assert_eq!(view.input.value(), "");
+        assert_eq!(view.conversations[0].entries.len(), 1);
+    }
+
+    #[test]
+    fn keeps_the_prompt_when_the_provider_fails() {
+        let mut view = view("chat-view-failure");
+        let mut store = view.store.borrow_mut();
+        store.instances.insert("Local".to_string(), ProviderInstance { name: "Local".to_string(), provider_type: ProviderType::Ollama });
+        store.settings.insert(
+            "Local".to_string(),
+            ProviderSettings {
+                api_entry_point: Some("127.0.0.1:1".to_string()),
+                retry: RetryPolicy { max_retries: 1, initial_backoff_ms: 1, max_backoff_ms: 1 },
+                ..ProviderSettings::default()
+            },
+        );
+        drop(store);
+
+        let notified = type_and_send(&mut view, "hello");
+        assert_eq!(notified.len(), 2, "{:?}", notified);
+        assert!(notified[0].starts_with("Local retry #1 connection"), "{}", notified[0]);
+        assert!(notified[1].starts_with("Local failed: connection"), "{}", notified[1]);
+        assert_eq!(view.input.value(), "");
+        let entries = &view.conversations[0].entries;
+        assert_eq!((entries.len(), entries[0].message.content.as_str()), (1, "hello"));
+    }
+
+    fn copied(view: &mut ChatView, binding: &str) -> Option<String> {
+        match view.handle_input(key(KeyCode::Null), Some(binding), &mut Notifications::default()) {
+            Action::Copy(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn copies_selected_message_and_code_blocks() {
+        let (store, ledger) = snapshot::store("chat-copy");
+        let mut view = ChatView::new(store, ledger);
+        assert_eq!(copied(&mut view, "copy_message"), None);
+
+        let answer = "Run this:\n```rust\nfn main() {}\n```\nthen\n```sh\ncargo run";
+        view.conversations[0].entries.push(ChatEntry::new(Role::User, "show me".to_string()));
+        view.conversations[0].entries.push(ChatEntry::new(Role::Assistant, answer.to_string()));
+        assert_eq!(copied(&mut view, "copy_message").as_deref(), Some(answer));
+        assert_eq!(copied(&mut view, "copy_code").as_deref(), Some("fn main() {}\ncargo run"));
+
+        view.handle_input(key(KeyCode::Up), Some("select_prev"), &mut Notifications::default());
+        assert_eq!(copied(&mut view, "copy_message").as_deref(), Some("show me"));
+        assert_eq!(copied(&mut view, "copy_code"), None);
+        view.handle_input(key(KeyCode::Down), Some("select_next"), &mut Notifications::default());
+        view.handle_input(key(KeyCode::Down), Some("select_next"), &mut Notifications::default());
+        assert_eq!(view.selected_entry, Some(1));
+    }
+
+    #[test]
+    fn edits_draft_in_external_editor() {
+        let (store, ledger) = snapshot::store("chat-edit");
+        let mut view = ChatView::new(store, ledger);
+        view.input.set_value("draft");
+        let action = view.handle_input(key(KeyCode::Null), Some("edit"), &mut Notifications::default());
+        assert!(matches!(action, Action::Edit(EditTarget::Text(text)) if text == "draft"));
+
+        view.focused = false;
+        view.update(Update::Edited(Ok("longer\ndraft\n".to_string())));
+        assert_eq!(view.input.value(), "longer\ndraft");
+        assert!(view.focused);
+        let action = view.update(Update::Edited(Err("vi exited with exit status: 1".to_string())));
+        assert!(matches!(action, Action::Notify(_, message) if message == "Edit failed: vi exited with exit status: 1"));
+        assert_eq!(view.input.value(), "longer\ndraft");
+    }
+
+    #[test]
+    fn builds_hints_from_the_keymap() {
+        let (store, ledger) = snapshot::store("chat-hints");
+        let mut view = ChatView::new(store, ledger);
+        view.set_keymap(&Keymap::preset("emacs").unwrap());
+        assert_eq!(view.prompt_titles[1], "Prompt [enter] Send  [alt-enter] Newline  [esc] Leave");
+        assert_eq!(view.conversation_hints[1], "[ctrl-x n] new  [ctrl-left] prev  [ctrl-right] next");
+
+        view.handle_input(key(KeyCode::Char('a')), None, &mut Notifications::default());
+        view.handle_input(key(KeyCode::Enter), Some("newline"), &mut Notifications::default());
+        assert_eq!(view.input.value(), "a\n");
+    }
+}
@@ -0,0 +1,116 @@
+use std::env;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+static REGISTER: Mutex<String> = Mutex::new(String::new());
+
+pub fn copy(text: &str) {
+    if let Ok(mut register) = REGISTER.lock() {
+        *register = text.to_string();
+    }
+}
+
+pub fn paste() -> String {
+    REGISTER.lock().map(|register| register.clone()).unwrap_or_default()
+}
+
+pub fn copy_to_system(text: &str) -> Result<&'static str, String> {
+    copy(text);
+    let set = |var: &str| env::var_os(var).is_some_and(|value| !value.is_empty());
+    if !set("SSH_TTY") && !set("SSH_CONNECTION") {
+        for command in commands(set("WAYLAND_DISPLAY"), set("DISPLAY"), cfg!(target_os = "macos")) {
+            if run(command, text).is_ok() {
+                return Ok(command[0]);
+            }
+        }
+    }
+    let mut stdout = io::stdout();
+    stdout
+        .write_all(osc52(text, set("TMUX")).as_bytes())
+        .and_then(|()| stdout.flush())
+        .map_err(|e| format!("failed to write OSC 52: {}", e))?;
+    Ok("OSC 52")
+}
+
+fn commands(wayland: bool, x11: bool, macos: bool) -> Vec<&'static [&'static str]> {
+    let mut commands: Vec<&'static [&'static str]> = Vec::new();
+    if macos {
+        commands.push(&["pbcopy"]);
+    }
+    if wayland {
+        commands.push(&["wl-copy"]);
+    }
+    if x11 {
+        commands.push(&["xclip", "-selection", "clipboard"]);
+        commands.push(&["xsel", "--clipboard", "--input"]);
+    }
+    commands
+}
+
+fn run(command: &[&str], text: &str) -> io::Result<()> {
+    let mut child = Command::new(command[0])
+        .args(&command[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{} exited with {}", command[0], status)))
+    }
+}
+
+pub fn osc52(text: &str, tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", base64(text.as_bytes()));
+    if tmux {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (index, byte)| group | (*byte as u32) << (16 - 8 * index));
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(BASE64[(group >> (18 - 6 * index) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{base64, commands, osc52};
+
+    #[test]
+    fn encodes_osc52_for_plain_and_tmux_terminals() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64("fn main() {}\n".as_bytes()), "Zm4gbWFpbigpIHt9Cg==");
+        assert_eq!(osc52("hi", false), "\x1b]52;c;aGk=\x07");
+        assert_eq!(osc52("hi", true), "\x1bPtmux;\x1b\x1b]52;c;aGk=\x07\x1b\\");
+    }
+
+    #[test]
+    fn prefers_display_server_commands() {
+        assert!(commands(false, false, false).is_empty());
+        assert_eq!(commands(true, true, false)[0], &["wl-copy"]);
+        assert_eq!(commands(false, true, false)[1][0], "xsel");
+    }
+}
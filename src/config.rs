@@ -0,0 +1,176 @@
+use crate::provider::{ProviderSettings, ProviderType};
+use crate::routing::Route;
+use crate::secret;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+pub fn config_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var("AI_CONFIG_DIR") {
+        return PathBuf::from(dir);
+    }
+    if let Ok(dir) = std::env::var("XDG_CONFIG_HOME") {
+        return PathBuf::from(dir).join("ai");
+    }
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".config").join("ai")
+}
+
+pub fn providers_path() -> PathBuf {
+    config_dir().join("providers.yaml")
+}
+
+pub fn vault_path() -> PathBuf {
+    config_dir().join("vault.bin")
+}
+
+pub fn usage_config_path() -> PathBuf {
+    config_dir().join("usage.yaml")
+}
+
+pub fn usage_log_path() -> PathBuf {
+    config_dir().join("usage.jsonl")
+}
+
+pub fn keymap_path() -> PathBuf {
+    config_dir().join("keymap.yaml")
+}
+
+pub fn theme_path() -> PathBuf {
+    config_dir().join("theme.yaml")
+}
+
+pub fn themes_dir() -> PathBuf {
+    config_dir().join("themes")
+}
+
+pub fn layout_path() -> PathBuf {
+    config_dir().join("layout.yaml")
+}
+
+pub fn crash_dir() -> PathBuf {
+    config_dir().join("crashes")
+}
+
+pub fn log_path() -> PathBuf {
+    config_dir().join("logs").join("ai.log")
+}
+
+/// Writes `contents` to a sibling temp file, syncs it and renames it over
+/// `path`, so readers never see a truncated file. `mode` applies on unix.
+pub fn write_atomic(path: &Path, contents: &[u8], mode: u32) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    let temp = path.with_file_name(name);
+    let _ = fs::remove_file(&temp);
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, mode);
+    #[cfg(not(unix))]
+    let _ = mode;
+    let written = options.open(&temp).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    match written.and_then(|()| fs::rename(&temp, path)) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            let _ = fs::remove_file(&temp);
+            Err(e)
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ProvidersConfig {
+    #[serde(default)]
+    pub providers: Vec<ProviderEntry>,
+    #[serde(default)]
+    pub routes: Vec<Route>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProviderEntry {
+    pub name: String,
+    pub provider_type: ProviderType,
+    pub settings: ProviderSettings,
+}
+
+impl ProvidersConfig {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_yaml::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(entry) = self
+            .providers
+            .iter()
+            .find(|entry| entry.settings.api_key.as_deref().is_some_and(|key| !key.is_empty() && !secret::is_reference(key)))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("refusing to write the plaintext API key of {}, store it in the vault", entry.name),
+            ));
+        }
+        let content = serde_yaml::to_string(self).map_err(io::Error::other)?;
+        write_atomic(path, content.as_bytes(), 0o666)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_atomic, ProviderEntry, ProvidersConfig};
+    use crate::provider::{ProviderSettings, ProviderType};
+    use crate::snapshot;
+
+    fn entry(name: &str, api_key: &str) -> ProviderEntry {
+        ProviderEntry {
+            name: name.to_string(),
+            provider_type: ProviderType::OpenAI,
+            settings: ProviderSettings { api_key: Some(api_key.to_string()), ..ProviderSettings::default() },
+        }
+    }
+
+    #[test]
+    fn keeps_secret_references_across_save_and_load() {
+        let path = snapshot::temp_dir("config-round-trip").join("providers.yaml");
+        let keys = ["${OPENAI_API_KEY}", "sk-${TEAM}-key", "env:OPENAI_API_KEY", "cmd:pass show openai", "vault:Work"];
+        let config = ProvidersConfig {
+            providers: keys.iter().enumerate().map(|(i, key)| entry(&format!("Provider{}", i), key)).collect(),
+            routes: Vec::new(),
+        };
+        config.save(&path).unwrap();
+
+        let loaded = ProvidersConfig::load(&path).unwrap();
+        let loaded: Vec<_> = loaded.providers.iter().map(|entry| entry.settings.api_key.as_deref().unwrap()).collect();
+        assert_eq!(loaded, keys);
+    }
+
+    #[test]
+    fn refuses_to_write_plaintext_keys() {
+        let path = snapshot::temp_dir("config-plaintext").join("providers.yaml");
+        let config = ProvidersConfig { providers: vec![entry("Work", "sk-plain")], routes: Vec::new() };
+        let error = config.save(&path).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn replaces_files_atomically() {
+        let path = snapshot::temp_dir("config-atomic").join("nested").join("providers.yaml");
+        write_atomic(&path, b"first", 0o666).unwrap();
+        write_atomic(&path, b"second", 0o666).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+        assert!(!path.with_file_name("providers.yaml.tmp").exists());
+    }
+}
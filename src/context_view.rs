@@ -1,46 +1,96 @@
-use crate::traits::View;
+use crate::editor::EditTarget;
+use crate::mouse;
+use crate::notify::{Notifications, Severity};
+use crate::theme::Theme;
+use crate::traits::{Action, PaletteEntry, Update, View};
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
-    style::{Color, Modifier, Style},
+    layout::{Constraint, Direction, Layout, Rect},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    widgets::{List, ListItem, ListState, Paragraph},
     Frame,
 };
+use std::cell::Cell;
 use std::fs;
-use crossterm::event::KeyCode;
+use std::path::PathBuf;
 
 pub struct ContextView {
+    dir: PathBuf,
     context_files: Vec<&'static str>,
     selected_context: usize,
     context_list_state: ListState,
     file_content: String,
+    list_width: u16,
+    scroll: u16,
+    dragging: bool,
+    area: Cell<Rect>,
+    list_area: Cell<Rect>,
+    list_offset: Cell<usize>,
+    content_area: Cell<Rect>,
 }
 
 impl ContextView {
     pub fn new() -> Self {
+        Self::in_dir(PathBuf::new())
+    }
+
+    pub fn in_dir(dir: PathBuf) -> Self {
         let context_files = vec!["file1.txt", "file2.txt", "file3.txt"];
         let mut context_list_state = ListState::default();
         context_list_state.select(Some(0));
-        let file_content = fs::read_to_string(context_files[0]).unwrap_or_default();
+        let file_content = fs::read_to_string(dir.join(context_files[0])).unwrap_or_default();
 
         Self {
+            dir,
             context_files,
             selected_context: 0,
             context_list_state,
             file_content,
+            list_width: 15,
+            scroll: 0,
+            dragging: false,
+            area: Cell::default(),
+            list_area: Cell::default(),
+            list_offset: Cell::default(),
+            content_area: Cell::default(),
+        }
+    }
+
+    fn selected_path(&self) -> PathBuf {
+        self.dir.join(self.context_files[self.selected_context])
+    }
+
+    fn select(&mut self, selected: usize) {
+        if selected != self.selected_context {
+            self.selected_context = selected;
+            self.context_list_state.select(Some(self.selected_context));
+            self.file_content = fs::read_to_string(self.selected_path()).unwrap_or_default();
+            self.scroll = 0;
         }
     }
 }
 
 impl View for ContextView {
-    fn render(&self, f: &mut Frame, area: ratatui::layout::Rect, _info_message: &str)
+    fn title(&self) -> &'static str {
+        "Context View"
+    }
+
+    fn shortcut(&self) -> char {
+        '1'
+    }
+
+    fn hints(&self) -> &'static [(&'static str, &'static str)] {
+        &[("select_next", "Next file"), ("select_prev", "Previous file"), ("grow_split", "Grow"), ("shrink_split", "Shrink"), ("copy", "Copy"), ("edit", "Edit")]
+    }
+
+    fn render(&self, f: &mut Frame, area: ratatui::layout::Rect, theme: &Theme)
     {
         let cols = Layout::default()
             .direction(Direction::Horizontal)
             .constraints(
                 [
-                    Constraint::Percentage(15),
-                    Constraint::Percentage(85),
+                    Constraint::Percentage(self.list_width),
+                    Constraint::Percentage(100 - self.list_width),
                 ]
                     .as_ref(),
             )
@@ -52,36 +102,195 @@ impl View for ContextView {
         }).collect();
 
         let list = List::new(items)
-            .block(Block::default().borders(Borders::ALL).title("Contexts"))
-            .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+            .block(theme.block("Contexts"))
+            .highlight_style(theme.highlight)
             .highlight_symbol(">> ");
 
-        f.render_stateful_widget(list, cols[0], &mut self.context_list_state.clone());
+        let mut list_state = self.context_list_state.clone();
+        f.render_stateful_widget(list, cols[0], &mut list_state);
 
         let file_view = Paragraph::new(self.file_content.as_str())
-            .block(Block::default().borders(Borders::ALL).title("Content"))
-            .style(Style::default().fg(Color::White));
+            .block(theme.block("Content"))
+            .style(theme.text)
+            .scroll((self.scroll, 0));
 
         f.render_widget(file_view, cols[1]);
+
+        self.area.set(area);
+        self.list_area.set(cols[0]);
+        self.list_offset.set(list_state.offset());
+        self.content_area.set(cols[1]);
     }
 
-    fn handle_input(&mut self, key: crossterm::event::KeyEvent, info_message: &mut String) {
-        match key.code {
-            KeyCode::Down => {
-                if self.selected_context < self.context_files.len() - 1 {
-                    self.selected_context += 1;
-                    self.context_list_state.select(Some(self.selected_context));
-                    self.file_content = fs::read_to_string(self.context_files[self.selected_context]).unwrap_or_default();
-                }
+    fn keymap_scope(&self) -> &'static str {
+        "context"
+    }
+
+    fn handle_input(&mut self, _key: crossterm::event::KeyEvent, binding: Option<&str>, _notifications: &mut Notifications) -> Action {
+        let last = self.context_files.len() - 1;
+        let selected = match binding {
+            Some("select_next") => (self.selected_context + 1).min(last),
+            Some("select_prev") => self.selected_context.saturating_sub(1),
+            Some("select_first") => 0,
+            Some("select_last") => last,
+            Some("grow_split") => {
+                self.set_split(self.list_width + 5);
+                return Action::None;
+            }
+            Some("shrink_split") => {
+                self.set_split(self.list_width.saturating_sub(5));
+                return Action::None;
+            }
+            Some("copy") => return Action::Copy(self.file_content.clone()),
+            Some("edit") => return Action::Edit(EditTarget::File(self.selected_path())),
+            _ => return Action::None,
+        };
+        self.select(selected);
+        Action::None
+    }
+
+    fn handle_mouse(&mut self, event: MouseEvent, _notifications: &mut Notifications) -> Action {
+        let area = self.area.get();
+        let separator = self.list_area.get().right().saturating_sub(1);
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) if (separator..=separator + 1).contains(&event.column) && mouse::hit(area, &event) => {
+                self.dragging = true;
             }
-            KeyCode::Up => {
-                if self.selected_context > 0 {
-                    self.selected_context -= 1;
-                    self.context_list_state.select(Some(self.selected_context));
-                    self.file_content = fs::read_to_string(self.context_files[self.selected_context]).unwrap_or_default();
+            MouseEventKind::Drag(MouseButton::Left) if self.dragging && area.width > 0 => {
+                let width = (event.column.saturating_sub(area.x) + 1) as u32 * 100 / area.width as u32;
+                self.set_split(width as u16);
+            }
+            MouseEventKind::Up(MouseButton::Left) => self.dragging = false,
+            MouseEventKind::ScrollDown if mouse::hit(self.content_area.get(), &event) => {
+                let lines = self.file_content.lines().count() as u16;
+                self.scroll = (self.scroll + 1).min(lines.saturating_sub(1));
+            }
+            MouseEventKind::ScrollUp if mouse::hit(self.content_area.get(), &event) => {
+                self.scroll = self.scroll.saturating_sub(1);
+            }
+            _ => {
+                if let Some(index) = mouse::list_row(self.list_area.get(), self.list_offset.get(), self.context_files.len(), &event) {
+                    self.select(index);
                 }
             }
-            _ => {}
         }
+        Action::None
+    }
+
+    fn palette_entries(&self) -> Vec<PaletteEntry> {
+        self.context_files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| PaletteEntry { label: format!("Open {}", file), command: format!("open:{}", index) })
+            .collect()
+    }
+
+    fn run_command(&mut self, command: &str, _notifications: &mut Notifications) -> Action {
+        if let Some(index) = command.strip_prefix("open:").and_then(|index| index.parse::<usize>().ok()) {
+            if index < self.context_files.len() {
+                self.select(index);
+            }
+        }
+        Action::None
+    }
+
+    fn split(&self) -> Option<u16> {
+        Some(self.list_width)
+    }
+
+    fn set_split(&mut self, split: u16) {
+        self.list_width = split.clamp(10, 60);
+    }
+
+    fn on_enter(&mut self) {
+        self.file_content = fs::read_to_string(self.selected_path()).unwrap_or_default();
+    }
+
+    fn update(&mut self, update: Update) -> Action {
+        match update {
+            Update::Edited(Ok(content)) => {
+                self.file_content = content;
+                Action::Notify(Severity::Success, format!("Reloaded {}", self.context_files[self.selected_context]))
+            }
+            Update::Edited(Err(e)) => Action::Notify(Severity::Error, format!("Edit failed: {}", e)),
+            _ => Action::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ContextView;
+    use crate::keymap::Keymap;
+    use crate::snapshot::{self, click, key, mouse, Harness};
+    use crossterm::event::{KeyCode, MouseButton, MouseEventKind};
+    use std::fs;
+
+    fn view(name: &str) -> ContextView {
+        let dir = snapshot::temp_dir(name);
+        for (file, content) in [("file1.txt", "first file\n"), ("file2.txt", "second file\n"), ("file3.txt", "third file\n")] {
+            fs::write(dir.join(file), content).unwrap();
+        }
+        ContextView::in_dir(dir)
+    }
+
+    #[test]
+    fn renders_context_list() {
+        Harness::new(view("context-initial"), 60, 8).assert_snapshot("context_view_initial");
+    }
+
+    #[test]
+    fn moves_selection_with_arrows() {
+        let mut harness = Harness::new(view("context-arrows"), 60, 8);
+        harness
+            .keys(&[key(KeyCode::Down), key(KeyCode::Down), key(KeyCode::Down)])
+            .assert_snapshot("context_view_last_file")
+            .keys(&[key(KeyCode::Up)])
+            .assert_snapshot("context_view_second_file");
+    }
+
+    #[test]
+    fn vim_preset_jumps_with_sequences() {
+        let mut harness = Harness::new(view("context-vim"), 60, 8);
+        harness.keymap = Keymap::preset("vim").unwrap();
+        harness
+            .keys(&[key(KeyCode::Char('G'))])
+            .assert_snapshot("context_view_last_file")
+            .keys(&[key(KeyCode::Char('g'))])
+            .assert_snapshot("context_view_last_file")
+            .keys(&[key(KeyCode::Char('g'))])
+            .assert_snapshot("context_view_initial")
+            .keys(&[key(KeyCode::Char('j'))])
+            .assert_snapshot("context_view_second_file");
+    }
+
+    #[test]
+    fn selects_scrolls_and_resizes_with_mouse() {
+        let mut harness = Harness::new(view("context-mouse"), 60, 8);
+        harness.mouse(&[click(3, 3)]).assert_snapshot("context_view_last_file");
+
+        harness.subject.file_content = (1..=10).map(|line| format!("line {}\n", line)).collect();
+        harness
+            .mouse(&[mouse(MouseEventKind::ScrollDown, 30, 3), mouse(MouseEventKind::ScrollDown, 30, 3)])
+            .assert_snapshot("context_view_scrolled")
+            .mouse(&[
+                mouse(MouseEventKind::Down(MouseButton::Left), 8, 2),
+                mouse(MouseEventKind::Drag(MouseButton::Left), 29, 2),
+                mouse(MouseEventKind::Up(MouseButton::Left), 29, 2),
+            ])
+            .assert_snapshot("context_view_resized");
+    }
+
+    #[test]
+    fn resizes_list_with_keys() {
+        let mut harness = Harness::new(view("context-keys"), 60, 8);
+        harness.keys(&[key(KeyCode::End)]);
+        harness.subject.file_content = (1..=10).map(|line| format!("line {}\n", line)).collect();
+        harness.subject.scroll = 2;
+        harness
+            .keys(&[key(KeyCode::Char('>')); 7])
+            .assert_snapshot("context_view_resized")
+            .keys(&[key(KeyCode::Char('<')); 20]);
+        assert_eq!(harness.subject.list_width, 10);
     }
 }
@@ -0,0 +1,66 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EditTarget {
+    Text(String),
+    File(PathBuf),
+}
+
+pub fn command() -> Vec<String> {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .map(|value| value.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+        .find(|command| !command.is_empty())
+        .unwrap_or_else(|| vec!["vi".to_string()])
+}
+
+pub fn edit(command: &[String], target: &EditTarget) -> Result<String, String> {
+    let path = match target {
+        EditTarget::File(path) => path.clone(),
+        EditTarget::Text(text) => {
+            let path = env::temp_dir().join(format!("ai-prompt-{}.md", std::process::id()));
+            fs::write(&path, text).map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+            path
+        }
+    };
+    let status = Command::new(&command[0]).args(&command[1..]).arg(&path).status();
+    let content = fs::read_to_string(&path).map_err(|e| format!("failed to read {}: {}", path.display(), e));
+    if matches!(target, EditTarget::Text(_)) {
+        let _ = fs::remove_file(&path);
+    }
+    match status {
+        Ok(status) if status.success() => content,
+        Ok(status) => Err(format!("{} exited with {}", command[0], status)),
+        Err(e) => Err(format!("failed to start {}: {}", command[0], e)),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::{edit, EditTarget};
+    use crate::snapshot;
+    use std::fs;
+
+    fn sh(script: &str) -> Vec<String> {
+        vec!["sh".to_string(), "-c".to_string(), script.to_string()]
+    }
+
+    #[test]
+    fn returns_edited_draft_and_file() {
+        let appended = sh("printf ' and more' >> \"$0\"");
+        assert_eq!(edit(&appended, &EditTarget::Text("draft".to_string())), Ok("draft and more".to_string()));
+
+        let path = snapshot::temp_dir("editor-file").join("notes.txt");
+        fs::write(&path, "notes").unwrap();
+        assert_eq!(edit(&appended, &EditTarget::File(path.clone())), Ok("notes and more".to_string()));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "notes and more");
+
+        let failed = edit(&sh("exit 3"), &EditTarget::Text("draft".to_string())).unwrap_err();
+        assert!(failed.starts_with("sh exited with"), "{}", failed);
+        assert!(edit(&["no-such-editor".to_string()], &EditTarget::Text(String::new())).is_err());
+    }
+}
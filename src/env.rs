@@ -0,0 +1,141 @@
+use crate::provider::ProviderType;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Debug, Default)]
+pub struct Env {
+    dotenv: BTreeMap<String, String>,
+}
+
+impl Env {
+    pub fn load() -> Self {
+        Self::from_file(Path::new(".env"))
+    }
+
+    pub fn from_file(path: &Path) -> Self {
+        let dotenv = fs::read_to_string(path).map(|content| parse_dotenv(&content)).unwrap_or_default();
+        Self { dotenv }
+    }
+
+    pub fn get(&self, var: &str) -> Option<(String, &'static str)> {
+        match std::env::var(var) {
+            Ok(value) if !value.is_empty() => Some((value, "env")),
+            _ => self.dotenv.get(var).map(|value| (value.clone(), ".env")),
+        }
+    }
+
+    pub fn interpolate(&self, value: &str) -> Result<String, String> {
+        let mut result = String::with_capacity(value.len());
+        let mut rest = value;
+        while let Some(start) = rest.find("${") {
+            result.push_str(&rest[..start]);
+            let Some(end) = rest[start..].find('}') else {
+                return Err(format!("unterminated variable in {}", value));
+            };
+            let expression = &rest[start + 2..start + end];
+            let (var, default) = match expression.split_once(":-") {
+                Some((var, default)) => (var, Some(default)),
+                None => (expression, None),
+            };
+            match (self.get(var), default) {
+                (Some((resolved, _)), _) => result.push_str(&resolved),
+                (None, Some(default)) => result.push_str(default),
+                (None, None) => return Err(format!("variable {} is not set", var)),
+            }
+            rest = &rest[start + end + 1..];
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+}
+
+fn parse_dotenv(content: &str) -> BTreeMap<String, String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+                .unwrap_or(value);
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+pub fn key_vars(provider_type: &ProviderType) -> &'static [&'static str] {
+    match provider_type {
+        ProviderType::OpenAI => &["OPENAI_API_KEY"],
+        ProviderType::Ollama => &[],
+        ProviderType::AzureOpenAI => &["AZURE_OPENAI_API_KEY"],
+        ProviderType::Gemini => &["GEMINI_API_KEY", "GOOGLE_API_KEY"],
+        ProviderType::Grog => &["GROQ_API_KEY"],
+        ProviderType::Claude => &["ANTHROPIC_API_KEY"],
+        ProviderType::OpenAICompatible => &[],
+        ProviderType::Mock => &[],
+    }
+}
+
+pub fn entry_point_vars(provider_type: &ProviderType) -> &'static [&'static str] {
+    match provider_type {
+        ProviderType::OpenAI => &["OPENAI_BASE_URL"],
+        ProviderType::Ollama => &["OLLAMA_HOST"],
+        ProviderType::AzureOpenAI => &["AZURE_OPENAI_ENDPOINT"],
+        ProviderType::Gemini => &[],
+        ProviderType::Grog => &["GROQ_BASE_URL"],
+        ProviderType::Claude => &["ANTHROPIC_BASE_URL"],
+        ProviderType::OpenAICompatible => &[],
+        ProviderType::Mock => &["AI_MOCK_FIXTURE"],
+    }
+}
+
+pub fn deployment_vars(provider_type: &ProviderType) -> &'static [&'static str] {
+    match provider_type {
+        ProviderType::AzureOpenAI => &["AZURE_OPENAI_DEPLOYMENT"],
+        _ => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Env;
+    use crate::snapshot;
+    use std::fs;
+
+    fn env(name: &str, dotenv: &str) -> Env {
+        let path = snapshot::temp_dir(name).join(".env");
+        fs::write(&path, dotenv).unwrap();
+        Env::from_file(&path)
+    }
+
+    #[test]
+    fn parses_dotenv_files() {
+        let env = env("env-parse", "# comment\nexport AI_TEST_PLAIN=one\nAI_TEST_DOUBLE=\"two words\"\n AI_TEST_SINGLE = 'three' \n\nbroken line\n");
+        assert_eq!(env.get("AI_TEST_PLAIN"), Some(("one".to_string(), ".env")));
+        assert_eq!(env.get("AI_TEST_DOUBLE"), Some(("two words".to_string(), ".env")));
+        assert_eq!(env.get("AI_TEST_SINGLE"), Some(("three".to_string(), ".env")));
+        assert_eq!(env.get("AI_TEST_MISSING"), None);
+    }
+
+    #[test]
+    fn interpolates_variables_with_defaults() {
+        let env = env("env-interpolate", "AI_TEST_HOST=example.com\nAI_TEST_PORT=8443\n");
+        assert_eq!(env.interpolate("https://${AI_TEST_HOST}:${AI_TEST_PORT}/v1").unwrap(), "https://example.com:8443/v1");
+        assert_eq!(env.interpolate("${AI_TEST_HOST:-localhost}").unwrap(), "example.com");
+        assert_eq!(env.interpolate("http://${AI_TEST_UNSET:-localhost}:${AI_TEST_UNSET_PORT:-}").unwrap(), "http://localhost:");
+        assert_eq!(env.interpolate("no variables").unwrap(), "no variables");
+    }
+
+    #[test]
+    fn reports_missing_and_unterminated_variables() {
+        let env = env("env-missing", "");
+        assert_eq!(env.interpolate("key-${AI_TEST_UNSET}").unwrap_err(), "variable AI_TEST_UNSET is not set");
+        assert_eq!(env.interpolate("key-${AI_TEST_UNSET").unwrap_err(), "unterminated variable in key-${AI_TEST_UNSET");
+    }
+}
@@ -0,0 +1,61 @@
+pub struct Match {
+    pub score: i64,
+    pub positions: Vec<usize>,
+}
+
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<Match> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect();
+    let chars: Vec<char> = candidate.chars().collect();
+    let mut positions: Vec<usize> = Vec::with_capacity(query.len());
+    let mut score = 0;
+    let mut next = 0;
+    for wanted in &query {
+        let found = (next..chars.len()).find(|&index| chars[index].to_lowercase().eq(std::iter::once(*wanted)))?;
+        let word_start = found == 0
+            || !chars[found - 1].is_alphanumeric()
+            || (chars[found - 1].is_lowercase() && chars[found].is_uppercase());
+        score += 16;
+        if word_start {
+            score += 24;
+        }
+        match positions.last() {
+            Some(&last) if last + 1 == found => score += 16,
+            Some(&last) => score -= (found - last - 1).min(8) as i64,
+            None => score -= found.min(8) as i64,
+        }
+        positions.push(found);
+        next = found + 1;
+    }
+    score -= (chars.len() / 8) as i64;
+    Some(Match { score, positions })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fuzzy_match;
+
+    fn rank<'a>(query: &str, candidates: &[&'a str]) -> Vec<&'a str> {
+        let mut ranked: Vec<_> = candidates
+            .iter()
+            .filter_map(|candidate| fuzzy_match(query, candidate).map(|found| (found.score, *candidate)))
+            .collect();
+        ranked.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(b.1)));
+        ranked.into_iter().map(|(_, candidate)| candidate).collect()
+    }
+
+    #[test]
+    fn matches_subsequences_case_insensitively() {
+        let found = fuzzy_match("PrAd", "Provider View: Add").unwrap();
+        assert_eq!(found.positions, vec![0, 1, 15, 16]);
+        assert!(fuzzy_match("xyz", "Provider View: Add").is_none());
+        assert!(fuzzy_match("", "anything").is_some());
+    }
+
+    #[test]
+    fn ranks_word_starts_and_runs_first() {
+        let candidates = ["Chat View: Next model", "Context View: Select next", "Next theme", "Provider View: Edit"];
+        assert_eq!(rank("next", &candidates), vec!["Next theme", "Chat View: Next model", "Context View: Select next"]);
+        assert_eq!(rank("cvsn", &candidates), vec!["Context View: Select next"]);
+        assert_eq!(rank("nt", &candidates)[0], "Next theme");
+    }
+}
@@ -0,0 +1,168 @@
+use crate::chat::{self, ChatEvent, ChatMessage, Role};
+use crate::config;
+use crate::env::Env;
+use crate::store::ProviderStore;
+use crate::usage::UsageLedger;
+use chrono::Local;
+use std::io::{self, Read, Write};
+
+pub struct Options {
+    pub prompt: String,
+    pub target: Option<String>,
+}
+
+impl Options {
+    pub fn parse(args: &[String]) -> Option<Result<Self, String>> {
+        let (mut prompt, mut target) = (None, None);
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let (slot, stdin, missing) = match arg.as_str() {
+                "--prompt" | "-p" => (&mut prompt, true, "--prompt needs a value, use - to read stdin"),
+                "--target" | "-t" => (&mut target, false, "--target needs a provider or route:NAME"),
+                "--record" | "--replay" => {
+                    args.next();
+                    continue;
+                }
+                _ => return Some(Err(format!("unknown option {}", arg))),
+            };
+            match args.next() {
+                Some(value) if !value.starts_with('-') || (stdin && value == "-") => *slot = Some(value.clone()),
+                _ => return Some(Err(missing.to_string())),
+            }
+        }
+        Some(Ok(Self { prompt: prompt?, target }))
+    }
+}
+
+pub fn run(options: Options) -> Result<(), String> {
+    let prompt = if options.prompt == "-" {
+        let mut prompt = String::new();
+        io::stdin().read_to_string(&mut prompt).map_err(|e| e.to_string())?;
+        prompt
+    } else {
+        options.prompt
+    };
+
+    let mut store = ProviderStore::load(config::providers_path(), config::vault_path(), Env::load());
+    for error in store.config_error.iter().chain(&store.vault_error) {
+        eprintln!("{}", error);
+    }
+    let mut ledger = UsageLedger::load(&config::usage_config_path(), config::usage_log_path());
+    answer(&mut store, &mut ledger, options.target, prompt, &mut io::stdout())
+}
+
+fn answer(
+    store: &mut ProviderStore,
+    ledger: &mut UsageLedger,
+    target: Option<String>,
+    prompt: String,
+    stdout: &mut dyn Write,
+) -> Result<(), String> {
+    ledger.check_budget().map_err(|e| format!("Request blocked: {}", e))?;
+    let target = match target {
+        Some(target) => target,
+        None => store.targets().into_iter().next().ok_or("No provider configured")?,
+    };
+    let dispatch = store.prepare(&target, &prompt, None);
+    let messages = [ChatMessage { role: Role::User, content: prompt }];
+
+    let mut streamed = false;
+    let (provider, response) = chat::dispatch(&dispatch, &messages, &mut |event| match event {
+        ChatEvent::Chunk(chunk) => {
+            streamed = true;
+            let _ = write!(stdout, "{}", chunk);
+            let _ = stdout.flush();
+        }
+        event => eprintln!("{}", event.note().unwrap_or_default()),
+    })?;
+    if !streamed {
+        let _ = write!(stdout, "{}", response.content);
+    }
+    let _ = writeln!(stdout);
+    for call in &response.tool_calls {
+        let _ = writeln!(stdout, "tool_call {} {}", call.name, call.arguments);
+    }
+
+    let conversation = format!("headless-{}", Local::now().format("%Y%m%d%H%M%S"));
+    if let Err(e) = ledger.record(&provider, &response.model, &conversation, response.usage) {
+        eprintln!("Failed to record usage: {}", e);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{answer, Options};
+    use crate::env::Env;
+    use crate::provider::{ProviderInstance, ProviderSettings, ProviderType};
+    use crate::snapshot;
+    use crate::store::ProviderStore;
+    use crate::usage::{Budget, UsageLedger};
+    use std::fs;
+    use std::path::Path;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn setup(dir: &Path, fixture: Option<&str>) -> (ProviderStore, UsageLedger) {
+        let mut store = ProviderStore::load(dir.join("providers.yaml"), dir.join("vault.bin"), Env::default());
+        if let Some(fixture) = fixture {
+            let path = dir.join("fixture.yaml");
+            fs::write(&path, fixture).unwrap();
+            store.update(
+                ProviderInstance { name: "Mock".to_string(), provider_type: ProviderType::Mock },
+                ProviderSettings { api_entry_point: Some(path.display().to_string()), ..ProviderSettings::default() },
+            );
+        }
+        (store, UsageLedger::load(&dir.join("usage.yaml"), dir.join("usage.jsonl")))
+    }
+
+    #[test]
+    fn parses_prompt_and_target_flags() {
+        assert!(Options::parse(&args(&[])).is_none());
+        assert!(Options::parse(&args(&["--target", "Work", "--record", "session.jsonl"])).is_none());
+
+        let options = Options::parse(&args(&["-t", "route:fast", "--prompt", "hello"])).unwrap().unwrap();
+        assert_eq!((options.prompt.as_str(), options.target.as_deref()), ("hello", Some("route:fast")));
+        let options = Options::parse(&args(&["-p", "-"])).unwrap().unwrap();
+        assert_eq!((options.prompt.as_str(), options.target), ("-", None));
+
+        let error = |list: &[&str]| Options::parse(&args(list)).unwrap().err().unwrap();
+        assert_eq!(error(&["--prompt"]), "--prompt needs a value, use - to read stdin");
+        assert_eq!(error(&["-p", "--target", "x"]), "--prompt needs a value, use - to read stdin");
+        assert_eq!(error(&["-p", "-t"]), "--prompt needs a value, use - to read stdin");
+        assert_eq!(error(&["-p", "hi", "-t"]), "--target needs a provider or route:NAME");
+        assert_eq!(error(&["-p", "hi", "-t", "-"]), "--target needs a provider or route:NAME");
+        assert_eq!(error(&["-p", "hi", "--targte", "x"]), "unknown option --targte");
+    }
+
+    #[test]
+    fn prints_the_answer_and_records_usage() {
+        let dir = snapshot::temp_dir("headless-answer");
+        let fixture = "responses:\n  - chunks: [\"Hello \", \"there\"]\n    tool_calls:\n      - name: lookup\n        arguments: { city: Paris }\n";
+        let (mut store, mut ledger) = setup(&dir, Some(fixture));
+        let mut stdout = Vec::new();
+        answer(&mut store, &mut ledger, None, "hi".to_string(), &mut stdout).unwrap();
+        assert_eq!(String::from_utf8(stdout).unwrap(), "Hello there\ntool_call lookup {\"city\":\"Paris\"}\n");
+
+        let reloaded = UsageLedger::load(&dir.join("usage.yaml"), dir.join("usage.jsonl"));
+        assert_eq!(reloaded.total_where(|record| record.provider == "Mock").requests, 1);
+    }
+
+    #[test]
+    fn fails_when_the_request_cannot_be_answered() {
+        let dir = snapshot::temp_dir("headless-fail");
+        let (mut store, mut ledger) = setup(&dir, None);
+        let mut stdout = Vec::new();
+        assert_eq!(answer(&mut store, &mut ledger, None, "hi".to_string(), &mut stdout), Err("No provider configured".to_string()));
+
+        let (mut store, mut ledger) = setup(&dir, Some("responses:\n  - error: { status: 401, message: bad key }\n"));
+        assert!(answer(&mut store, &mut ledger, None, "hi".to_string(), &mut stdout).unwrap_err().contains("bad key"));
+
+        ledger.config.budget = Budget { daily: Some(0.0), monthly: None };
+        let error = answer(&mut store, &mut ledger, Some("Mock".to_string()), "hi".to_string(), &mut stdout).unwrap_err();
+        assert!(error.starts_with("Request blocked: daily budget"), "{}", error);
+        assert!(stdout.is_empty());
+    }
+}
@@ -0,0 +1,99 @@
+use crate::fuzzy::fuzzy_match;
+use crate::palette;
+use crate::theme::Theme;
+use crate::widgets::TextInput;
+use crossterm::event::KeyEvent;
+use std::cell::Cell;
+use ratatui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Clear, Paragraph},
+    Frame,
+};
+
+pub struct Section {
+    pub title: String,
+    pub keys: Vec<(String, String)>,
+}
+
+pub struct HelpOverlay {
+    title: String,
+    sections: Vec<Section>,
+    query: TextInput,
+    scroll: u16,
+    max_scroll: Cell<u16>,
+}
+
+impl HelpOverlay {
+    pub fn new(hints: String, sections: Vec<Section>) -> Self {
+        Self { title: format!("Help {}", hints), sections, query: TextInput::new(), scroll: 0, max_scroll: Cell::new(0) }
+    }
+
+    pub fn handle_input(&mut self, key: KeyEvent, binding: Option<&str>) -> bool {
+        match (binding, key.code) {
+            (Some("close"), _) => return false,
+            (Some("scroll_down"), _) => self.scroll = (self.scroll + 1).min(self.max_scroll.get()),
+            (Some("scroll_up"), _) => self.scroll = self.scroll.saturating_sub(1),
+            _ => {
+                if self.query.handle_input(key, binding) {
+                    self.scroll = 0;
+                }
+            }
+        }
+        true
+    }
+
+    pub fn handle_paste(&mut self, text: &str) {
+        self.query.insert_str(text);
+        self.scroll = 0;
+    }
+
+    fn lines(&self, theme: &Theme) -> Vec<Line<'_>> {
+        let width = self
+            .sections
+            .iter()
+            .flat_map(|section| section.keys.iter().map(|(keys, _)| keys.chars().count()))
+            .max()
+            .unwrap_or_default();
+        let mut lines = Vec::new();
+        for section in &self.sections {
+            let keys: Vec<_> = section
+                .keys
+                .iter()
+                .filter(|(keys, label)| fuzzy_match(self.query.value(), &format!("{} {}", label, keys)).is_some())
+                .collect();
+            if keys.is_empty() {
+                continue;
+            }
+            if !lines.is_empty() {
+                lines.push(Line::raw(""));
+            }
+            lines.push(Line::styled(section.title.as_str(), theme.highlight));
+            for (keys, label) in keys {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("  {:width$}  ", keys, width = width), theme.accent),
+                    Span::styled(label.as_str(), theme.text),
+                ]));
+            }
+        }
+        if lines.is_empty() {
+            lines.push(Line::styled("No matching keys", theme.muted));
+        }
+        lines
+    }
+
+    pub fn render(&self, f: &mut Frame, area: Rect, theme: &Theme) {
+        let lines = self.lines(theme);
+        let popup = palette::centered(area, (area.width * 7 / 10).max(50), lines.len() as u16 + 3);
+        f.render_widget(Clear, popup);
+        let block = theme.block(self.title.as_str());
+        let inner = block.inner(popup);
+        f.render_widget(block, popup);
+        let search = Rect { height: 1.min(inner.height), ..inner };
+        f.render_widget(Paragraph::new("Search: ").style(theme.text), search);
+        self.query.render(f, Rect { x: search.x + 8, width: search.width.saturating_sub(8), ..search }, theme.text, true);
+        let body = Rect { y: inner.y + search.height, height: inner.height - search.height, ..inner };
+        self.max_scroll.set((lines.len() as u16).saturating_sub(body.height));
+        f.render_widget(Paragraph::new(lines).scroll((self.scroll.min(self.max_scroll.get()), 0)), body);
+    }
+}
@@ -0,0 +1,526 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+type Preset = &'static [(&'static str, &'static str, &'static [&'static str])];
+
+const DEFAULT: Preset = &[
+    ("global", "next_view", &["tab"]),
+    ("global", "quit", &["esc"]),
+    ("global", "command", &[":"]),
+    ("global", "next_theme", &["f2"]),
+    ("global", "split_pane", &["ctrl-w v"]),
+    ("global", "focus_pane", &["ctrl-w w"]),
+    ("global", "grow_pane", &["ctrl-w >"]),
+    ("global", "shrink_pane", &["ctrl-w <"]),
+    ("global", "zoom_pane", &["ctrl-w z"]),
+    ("global", "palette", &["ctrl-p"]),
+    ("global", "help", &["?"]),
+    ("global", "dismiss_notification", &["ctrl-d"]),
+    ("help", "scroll_down", &["down"]),
+    ("help", "scroll_up", &["up"]),
+    ("help", "close", &["esc"]),
+    ("palette", "select_next", &["down", "ctrl-n"]),
+    ("palette", "select_prev", &["up"]),
+    ("palette", "confirm", &["enter"]),
+    ("palette", "cancel", &["esc", "ctrl-p"]),
+    ("context", "select_next", &["down"]),
+    ("context", "select_prev", &["up"]),
+    ("context", "select_first", &["home"]),
+    ("context", "select_last", &["end"]),
+    ("context", "grow_split", &[">"]),
+    ("context", "shrink_split", &["<"]),
+    ("context", "copy", &["y"]),
+    ("context", "edit", &["e"]),
+    ("log", "select_next", &["down"]),
+    ("log", "select_prev", &["up"]),
+    ("log", "select_first", &["home"]),
+    ("log", "select_last", &["end"]),
+    ("log", "cycle_level", &["l"]),
+    ("log", "toggle_follow", &["f"]),
+    ("provider", "add", &["a"]),
+    ("provider", "edit", &["e"]),
+    ("provider", "delete", &["d"]),
+    ("provider", "test", &["t"]),
+    ("provider", "select_next", &["down"]),
+    ("provider", "select_prev", &["up"]),
+    ("provider", "select_first", &["home"]),
+    ("provider", "select_last", &["end"]),
+    ("provider_form", "select_next", &["down"]),
+    ("provider_form", "select_prev", &["up"]),
+    ("provider_form", "next_field", &["tab"]),
+    ("provider_form", "prev_field", &["backtab"]),
+    ("provider_form", "reveal_key", &["ctrl-r"]),
+    ("provider_form", "confirm", &["enter"]),
+    ("provider_form", "cancel", &["esc"]),
+    ("modal", "accept", &["y"]),
+    ("modal", "reject", &["n"]),
+    ("modal", "toggle", &["tab", "left", "right"]),
+    ("modal", "confirm", &["enter"]),
+    ("modal", "cancel", &["esc"]),
+    ("chat", "focus", &["enter", "i"]),
+    ("chat", "next_target", &["ctrl-t"]),
+    ("chat", "next_model", &["ctrl-o"]),
+    ("chat", "new_conversation", &["ctrl-n"]),
+    ("chat", "prev_conversation", &["ctrl-left"]),
+    ("chat", "next_conversation", &["ctrl-right"]),
+    ("chat", "select_next", &["down"]),
+    ("chat", "select_prev", &["up"]),
+    ("chat", "copy_message", &["y"]),
+    ("chat", "copy_code", &["c"]),
+    ("chat", "edit", &["e", "ctrl-x ctrl-e"]),
+    ("chat_input", "send", &["enter"]),
+    ("chat_input", "newline", &["alt-enter"]),
+    ("chat_input", "unfocus", &["esc"]),
+    ("chat_input", "next_target", &["ctrl-t"]),
+    ("chat_input", "next_model", &["ctrl-o"]),
+    ("chat_input", "new_conversation", &["ctrl-n"]),
+    ("chat_input", "prev_conversation", &["ctrl-left"]),
+    ("chat_input", "next_conversation", &["ctrl-right"]),
+    ("chat_input", "edit", &["ctrl-x ctrl-e"]),
+];
+
+const VIM: Preset = &[
+    ("context", "select_next", &["j", "down"]),
+    ("context", "select_prev", &["k", "up"]),
+    ("context", "select_first", &["g g", "home"]),
+    ("context", "select_last", &["G", "end"]),
+    ("log", "select_next", &["j", "down"]),
+    ("log", "select_prev", &["k", "up"]),
+    ("log", "select_first", &["g g", "home"]),
+    ("log", "select_last", &["G", "end"]),
+    ("provider", "select_next", &["j", "down"]),
+    ("provider", "select_prev", &["k", "up"]),
+    ("provider", "select_first", &["g g", "home"]),
+    ("provider", "select_last", &["G", "end"]),
+    ("provider_form", "select_next", &["ctrl-j", "down"]),
+    ("provider_form", "select_prev", &["ctrl-k", "up"]),
+    ("chat", "focus", &["enter", "i", "a"]),
+    ("chat", "select_next", &["j", "down"]),
+    ("chat", "select_prev", &["k", "up"]),
+    ("chat", "new_conversation", &["ctrl-n", "g n"]),
+    ("chat", "prev_conversation", &["ctrl-left", "g T"]),
+    ("chat", "next_conversation", &["ctrl-right", "g t"]),
+];
+
+const EMACS: Preset = &[
+    ("global", "quit", &["esc", "ctrl-x ctrl-q"]),
+    ("global", "palette", &["alt-x"]),
+    ("palette", "select_prev", &["up", "ctrl-p"]),
+    ("palette", "cancel", &["esc", "ctrl-g"]),
+    ("help", "scroll_down", &["down", "ctrl-n"]),
+    ("help", "scroll_up", &["up", "ctrl-p"]),
+    ("help", "close", &["esc", "ctrl-g"]),
+    ("context", "select_next", &["ctrl-n", "down"]),
+    ("context", "select_prev", &["ctrl-p", "up"]),
+    ("context", "select_first", &["alt-<", "home"]),
+    ("context", "select_last", &["alt->", "end"]),
+    ("context", "copy", &["y", "alt-w"]),
+    ("log", "select_next", &["ctrl-n", "down"]),
+    ("log", "select_prev", &["ctrl-p", "up"]),
+    ("log", "select_first", &["alt-<", "home"]),
+    ("log", "select_last", &["alt->", "end"]),
+    ("provider", "select_next", &["ctrl-n", "down"]),
+    ("provider", "select_prev", &["ctrl-p", "up"]),
+    ("provider", "select_first", &["alt-<", "home"]),
+    ("provider", "select_last", &["alt->", "end"]),
+    ("provider_form", "select_next", &["ctrl-n", "down"]),
+    ("provider_form", "select_prev", &["ctrl-p", "up"]),
+    ("provider_form", "cancel", &["esc", "ctrl-g"]),
+    ("modal", "cancel", &["esc", "ctrl-g"]),
+    ("chat", "new_conversation", &["ctrl-x n"]),
+    ("chat", "copy_message", &["y", "alt-w"]),
+    ("chat", "prev_conversation", &["ctrl-left", "ctrl-x left"]),
+    ("chat", "next_conversation", &["ctrl-right", "ctrl-x right"]),
+    ("chat_input", "unfocus", &["esc", "ctrl-g"]),
+    ("chat_input", "new_conversation", &["ctrl-x n"]),
+    ("chat_input", "prev_conversation", &["ctrl-left", "ctrl-x left"]),
+    ("chat_input", "next_conversation", &["ctrl-right", "ctrl-x right"]),
+];
+
+/// Scopes the app resolves on their own, without falling through to "global".
+const STANDALONE_SCOPES: [&str; 5] = ["help", "palette", "modal", "provider_form", "chat_input"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = value;
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            let (modifier, len) = if lower.starts_with("ctrl-") && rest.len() > 5 {
+                (KeyModifiers::CONTROL, 5)
+            } else if lower.starts_with("alt-") && rest.len() > 4 {
+                (KeyModifiers::ALT, 4)
+            } else if lower.starts_with("shift-") && rest.len() > 6 {
+                (KeyModifiers::SHIFT, 6)
+            } else {
+                break;
+            };
+            modifiers |= modifier;
+            rest = &rest[len..];
+        }
+        let code = match rest.to_ascii_lowercase().as_str() {
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "space" => KeyCode::Char(' '),
+            name if name.len() > 1 && name.starts_with('f') && name[1..].parse::<u8>().is_ok() => {
+                KeyCode::F(name[1..].parse().unwrap_or_default())
+            }
+            _ if rest.chars().count() == 1 => KeyCode::Char(rest.chars().next().unwrap_or_default()),
+            _ => return Err(format!("unknown key {}", value)),
+        };
+        Ok(Self::normalized(code, modifiers))
+    }
+
+    fn normalized(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let modifiers = match code {
+            KeyCode::Char(_) | KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        Self { code, modifiers }
+    }
+}
+
+impl From<KeyEvent> for KeyChord {
+    fn from(key: KeyEvent) -> Self {
+        Self::normalized(key.code, key.modifiers)
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [(KeyModifiers::CONTROL, "ctrl-"), (KeyModifiers::ALT, "alt-"), (KeyModifiers::SHIFT, "shift-")] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            code => f.write_str(&format!("{:?}", code).to_ascii_lowercase()),
+        }
+    }
+}
+
+pub fn parse_sequence(value: &str) -> Result<Vec<KeyChord>, String> {
+    let sequence = value.split_whitespace().map(KeyChord::parse).collect::<Result<Vec<_>, _>>()?;
+    if sequence.is_empty() {
+        return Err("empty key sequence".to_string());
+    }
+    Ok(sequence)
+}
+
+pub fn format_sequence(sequence: &[KeyChord]) -> String {
+    sequence.iter().map(KeyChord::to_string).collect::<Vec<_>>().join(" ")
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct KeymapConfig {
+    pub preset: Option<String>,
+    pub bindings: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Binding {
+    pub scope: String,
+    pub action: String,
+    pub keys: Vec<KeyChord>,
+}
+
+pub enum Resolution<'a> {
+    Pending,
+    Bound(&'a Binding),
+    Unbound,
+}
+
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::preset("default").unwrap_or_else(|_| Self { bindings: Vec::new() })
+    }
+}
+
+impl Keymap {
+    pub fn preset(name: &str) -> Result<Self, String> {
+        let overrides = match name {
+            "default" => &[][..],
+            "vim" => VIM,
+            "emacs" => EMACS,
+            _ => return Err(format!("unknown keymap preset {}", name)),
+        };
+        let mut keymap = Self { bindings: Vec::new() };
+        for (scope, action, keys) in DEFAULT {
+            let keys = overrides
+                .iter()
+                .find(|(override_scope, override_action, _)| override_scope == scope && override_action == action)
+                .map(|(_, _, keys)| *keys)
+                .unwrap_or(keys);
+            for sequence in keys {
+                keymap.bindings.push(Binding {
+                    scope: scope.to_string(),
+                    action: action.to_string(),
+                    keys: parse_sequence(sequence)?,
+                });
+            }
+        }
+        Ok(keymap)
+    }
+
+    pub fn load(path: &Path) -> (Self, Vec<String>) {
+        let Ok(content) = fs::read_to_string(path) else {
+            return (Self::default(), Vec::new());
+        };
+        match serde_yaml::from_str::<KeymapConfig>(&content) {
+            Ok(config) => Self::from_config(config),
+            Err(e) => (Self::default(), vec![format!("invalid keymap {}: {}", path.display(), e)]),
+        }
+    }
+
+    pub fn from_config(config: KeymapConfig) -> (Self, Vec<String>) {
+        let mut warnings = Vec::new();
+        let mut keymap = match Self::preset(config.preset.as_deref().unwrap_or("default")) {
+            Ok(keymap) => keymap,
+            Err(e) => {
+                warnings.push(e);
+                Self::default()
+            }
+        };
+        for (scope, actions) in config.bindings {
+            for (action, keys) in actions {
+                let known = action.starts_with("show_") && scope == "global"
+                    || DEFAULT.iter().any(|(default_scope, default_action, _)| *default_scope == scope && *default_action == action);
+                if !known {
+                    warnings.push(format!("unknown keymap action {}.{}", scope, action));
+                    continue;
+                }
+                keymap.bindings.retain(|binding| binding.scope != scope || binding.action != action);
+                for sequence in keys {
+                    match parse_sequence(&sequence) {
+                        Ok(keys) => keymap.bindings.push(Binding { scope: scope.clone(), action: action.clone(), keys }),
+                        Err(e) => warnings.push(format!("{}.{}: {}", scope, action, e)),
+                    }
+                }
+            }
+        }
+        (keymap, warnings)
+    }
+
+    pub fn actions(&self, scope: &str) -> Vec<(&str, Vec<String>)> {
+        let mut actions: Vec<(&str, Vec<String>)> = Vec::new();
+        for binding in self.bindings.iter().filter(|binding| binding.scope == scope) {
+            let keys = format_sequence(&binding.keys);
+            match actions.iter_mut().find(|(action, _)| *action == binding.action) {
+                Some((_, all)) => all.push(keys),
+                None => actions.push((&binding.action, vec![keys])),
+            }
+        }
+        actions
+    }
+
+    pub fn hints(&self, scope: &str, actions: &[(&str, &str)]) -> String {
+        actions
+            .iter()
+            .filter_map(|(action, label)| {
+                let binding = self.bindings.iter().find(|binding| binding.scope == scope && binding.action == *action)?;
+                Some(format!("[{}] {}", format_sequence(&binding.keys), label))
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+
+    pub fn bind_default(&mut self, scope: &str, action: &str, sequence: &str) {
+        if self.bindings.iter().any(|binding| binding.scope == scope && binding.action == action) {
+            return;
+        }
+        if let Ok(keys) = parse_sequence(sequence) {
+            self.bindings.push(Binding { scope: scope.to_string(), action: action.to_string(), keys });
+        }
+    }
+
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        for (index, first) in self.bindings.iter().enumerate() {
+            for second in &self.bindings[index + 1..] {
+                let (view, global) = if first.scope == "global" { (second, first) } else { (first, second) };
+                let checked = if view.scope == global.scope {
+                    view.action != global.action
+                } else {
+                    global.scope == "global" && !STANDALONE_SCOPES.contains(&view.scope.as_str())
+                };
+                if !checked {
+                    continue;
+                }
+                let (shorter, longer) = if first.keys.len() <= second.keys.len() { (first, second) } else { (second, first) };
+                if !longer.keys.starts_with(&shorter.keys) {
+                    continue;
+                }
+                if view.scope == global.scope {
+                    conflicts.push(format!(
+                        "{}: '{}' ({}) conflicts with '{}' ({})",
+                        first.scope,
+                        format_sequence(&shorter.keys),
+                        shorter.action,
+                        format_sequence(&longer.keys),
+                        longer.action
+                    ));
+                } else {
+                    conflicts.push(format!(
+                        "{}: '{}' ({}) conflicts with global '{}' ({})",
+                        view.scope,
+                        format_sequence(&view.keys),
+                        view.action,
+                        format_sequence(&global.keys),
+                        global.action
+                    ));
+                }
+            }
+        }
+        conflicts
+    }
+
+    pub fn feed(&self, pending: &mut Vec<KeyChord>, scopes: &[&str], key: KeyEvent) -> Resolution<'_> {
+        pending.push(KeyChord::from(key));
+        let candidates = || {
+            scopes
+                .iter()
+                .flat_map(|scope| self.bindings.iter().filter(move |binding| binding.scope == *scope))
+        };
+        if let Some(binding) = candidates().find(|binding| binding.keys == *pending) {
+            pending.clear();
+            return Resolution::Bound(binding);
+        }
+        if candidates().any(|binding| binding.keys.starts_with(pending)) {
+            return Resolution::Pending;
+        }
+        let retry = pending.len() > 1;
+        pending.clear();
+        if retry {
+            return self.feed(pending, scopes, key);
+        }
+        Resolution::Unbound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KeyChord, Keymap, KeymapConfig, Resolution};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    fn config(yaml: &str) -> KeymapConfig {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn parses_and_formats_chords() {
+        for chord in ["ctrl-t", "alt-<", "backtab", "f5", "space", "G", "ctrl-left"] {
+            assert_eq!(KeyChord::parse(chord).unwrap().to_string(), chord);
+        }
+        assert_eq!(KeyChord::parse("CTRL-t").unwrap(), KeyChord::parse("ctrl-t").unwrap());
+        assert!(KeyChord::parse("hyper-x").is_err());
+        let shifted = KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert_eq!(KeyChord::from(shifted), KeyChord::parse("G").unwrap());
+    }
+
+    #[test]
+    fn presets_have_no_conflicts() {
+        for preset in ["default", "vim", "emacs"] {
+            assert_eq!(Keymap::preset(preset).unwrap().conflicts(), Vec::<String>::new(), "{}", preset);
+        }
+    }
+
+    #[test]
+    fn resolves_multi_key_sequences() {
+        let keymap = Keymap::preset("vim").unwrap();
+        let mut pending = Vec::new();
+        let g = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE);
+        let x = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE);
+        assert!(matches!(keymap.feed(&mut pending, &["context"], g), Resolution::Pending));
+        assert!(matches!(keymap.feed(&mut pending, &["context"], g), Resolution::Bound(binding) if binding.action == "select_first"));
+        assert!(matches!(keymap.feed(&mut pending, &["context"], g), Resolution::Pending));
+        assert!(matches!(keymap.feed(&mut pending, &["context"], x), Resolution::Bound(binding) if binding.action == "select_next"));
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn emacs_keeps_ctrl_p_for_moving_up() {
+        let keymap = Keymap::preset("emacs").unwrap();
+        let ctrl_p = KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL);
+        let alt_x = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT);
+        for scope in ["context", "log", "provider"] {
+            let resolved = keymap.feed(&mut Vec::new(), &[scope, "global"], ctrl_p);
+            assert!(matches!(resolved, Resolution::Bound(binding) if binding.action == "select_prev"), "{}", scope);
+        }
+        let resolved = keymap.feed(&mut Vec::new(), &["provider", "global"], alt_x);
+        assert!(matches!(resolved, Resolution::Bound(binding) if binding.action == "palette"));
+    }
+
+    #[test]
+    fn reports_conflicts_and_unknown_actions() {
+        let (keymap, warnings) = Keymap::from_config(config(
+            "preset: vim\nbindings:\n  context:\n    select_next: [g]\n    jump: [x]\n  provider:\n    add: [\"ctrl-\"]\n",
+        ));
+        assert_eq!(warnings, vec!["unknown keymap action context.jump".to_string(), "provider.add: unknown key ctrl-".to_string()]);
+        assert_eq!(keymap.conflicts(), vec!["context: 'g' (select_next) conflicts with 'g g' (select_first)".to_string()]);
+    }
+
+    #[test]
+    fn reports_view_bindings_that_shadow_global_ones() {
+        let (keymap, warnings) = Keymap::from_config(config(
+            "bindings:\n  provider:\n    add: [\"ctrl-w\"]\n  log:\n    cycle_level: [\"? l\"]\n  chat_input:\n    send: [tab]\n  global:\n    palette: [\"ctrl-p\"]\n",
+        ));
+        assert!(warnings.is_empty());
+        assert_eq!(
+            keymap.conflicts(),
+            vec![
+                "provider: 'ctrl-w' (add) conflicts with global 'ctrl-w v' (split_pane)".to_string(),
+                "provider: 'ctrl-w' (add) conflicts with global 'ctrl-w w' (focus_pane)".to_string(),
+                "provider: 'ctrl-w' (add) conflicts with global 'ctrl-w >' (grow_pane)".to_string(),
+                "provider: 'ctrl-w' (add) conflicts with global 'ctrl-w <' (shrink_pane)".to_string(),
+                "provider: 'ctrl-w' (add) conflicts with global 'ctrl-w z' (zoom_pane)".to_string(),
+                "log: '? l' (cycle_level) conflicts with global '?' (help)".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn formats_hints_from_current_bindings() {
+        let (keymap, _) = Keymap::from_config(config("preset: emacs\nbindings:\n  provider:\n    add: [n]\n"));
+        assert_eq!(keymap.hints("provider", &[("add", "Add"), ("delete", "Delete"), ("missing", "Missing")]), "[n] Add  [d] Delete");
+        assert_eq!(keymap.hints("chat_input", &[("unfocus", "Leave")]), "[esc] Leave");
+        assert_eq!(keymap.hints("global", &[("palette", "Palette")]), "[alt-x] Palette");
+    }
+
+    #[test]
+    fn unknown_preset_falls_back_to_default() {
+        let (keymap, warnings) = Keymap::from_config(config("preset: nano\n"));
+        assert_eq!(warnings, vec!["unknown keymap preset nano".to_string()]);
+        assert_eq!(keymap.conflicts(), Vec::<String>::new());
+    }
+}
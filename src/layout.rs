@@ -0,0 +1,157 @@
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const MIN_SPLIT: u16 = 20;
+const MAX_SPLIT: u16 = 80;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LayoutConfig {
+    #[serde(default)]
+    pub panes: Vec<char>,
+    #[serde(default)]
+    pub focus: usize,
+    #[serde(default = "default_split")]
+    pub split: u16,
+    #[serde(default)]
+    pub zoomed: bool,
+    #[serde(default)]
+    pub view_splits: BTreeMap<char, u16>,
+}
+
+fn default_split() -> u16 {
+    50
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self { panes: Vec::new(), focus: 0, split: default_split(), zoomed: false, view_splits: BTreeMap::new() }
+    }
+}
+
+impl LayoutConfig {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_yaml::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let content = serde_yaml::to_string(self).map_err(io::Error::other)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, content)
+    }
+}
+
+pub struct Panes {
+    views: Vec<usize>,
+    focus: usize,
+    split: u16,
+    pub zoomed: bool,
+}
+
+impl Default for Panes {
+    fn default() -> Self {
+        Self { views: vec![0], focus: 0, split: default_split(), zoomed: false }
+    }
+}
+
+impl Panes {
+    pub fn active(&self) -> usize {
+        self.views[self.focus]
+    }
+
+    pub fn views(&self) -> &[usize] {
+        &self.views
+    }
+
+    pub fn is_split(&self) -> bool {
+        self.views.len() > 1
+    }
+
+    pub fn show(&mut self, view: usize) {
+        match self.views.iter().position(|&shown| shown == view) {
+            Some(pane) => self.focus = pane,
+            None => self.views[self.focus] = view,
+        }
+    }
+
+    pub fn focus(&mut self, pane: usize) {
+        if pane < self.views.len() {
+            self.focus = pane;
+        }
+    }
+
+    pub fn cycle_focus(&mut self) {
+        self.focus = (self.focus + 1) % self.views.len();
+    }
+
+    pub fn toggle_split(&mut self, count: usize) {
+        if self.is_split() {
+            self.views = vec![self.active()];
+            self.focus = 0;
+        } else if count > 1 {
+            self.views.push((self.active() + 1) % count);
+        }
+    }
+
+    pub fn resize(&mut self, delta: i16) {
+        let delta = if self.focus == 0 { delta } else { -delta };
+        self.set_split(self.split.saturating_add_signed(delta));
+    }
+
+    pub fn set_split(&mut self, split: u16) {
+        self.split = split.clamp(MIN_SPLIT, MAX_SPLIT);
+    }
+
+    pub fn areas(&self, area: Rect) -> Vec<Rect> {
+        if !self.is_split() || self.zoomed {
+            return vec![area];
+        }
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(self.split), Constraint::Percentage(100 - self.split)])
+            .split(area)
+            .to_vec()
+    }
+
+    pub fn visible(&self) -> Vec<usize> {
+        if self.zoomed {
+            vec![self.active()]
+        } else {
+            self.views.clone()
+        }
+    }
+
+    pub fn from_config(config: &LayoutConfig, shortcuts: &[char]) -> Self {
+        let views: Vec<usize> = config
+            .panes
+            .iter()
+            .filter_map(|shortcut| shortcuts.iter().position(|candidate| candidate == shortcut))
+            .take(2)
+            .collect();
+        if views.is_empty() || (views.len() == 2 && views[0] == views[1]) {
+            return Self { zoomed: config.zoomed, ..Self::default() };
+        }
+        let mut panes = Self { focus: config.focus.min(views.len() - 1), views, split: default_split(), zoomed: config.zoomed };
+        panes.set_split(config.split);
+        panes
+    }
+
+    pub fn to_config(&self, shortcuts: &[char]) -> LayoutConfig {
+        LayoutConfig {
+            panes: self.views.iter().map(|&view| shortcuts[view]).collect(),
+            focus: self.focus,
+            split: self.split,
+            zoomed: self.zoomed,
+            view_splits: BTreeMap::new(),
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use crate::logging::{self, Level, LogRecord};
+use crate::mouse;
+use crate::notify::Notifications;
+use crate::theme::Theme;
+use crate::traits::{Action, View};
+use crossterm::event::{KeyEvent, MouseEvent, MouseEventKind};
+use ratatui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{List, ListItem, ListState},
+    Frame,
+};
+use serde_json::Value;
+use std::cell::Cell;
+
+const MAX_RECORDS: usize = 2000;
+
+pub struct LogView {
+    records: Vec<LogRecord>,
+    last_seq: u64,
+    min_level: Level,
+    follow: bool,
+    selected: usize,
+    area: Cell<Rect>,
+}
+
+impl LogView {
+    pub fn new() -> Self {
+        Self { records: Vec::new(), last_seq: 0, min_level: Level::Info, follow: true, selected: 0, area: Cell::new(Rect::default()) }
+    }
+
+    pub fn append(&mut self, records: Vec<LogRecord>) {
+        if let Some(last) = records.last() {
+            self.last_seq = last.seq;
+        }
+        self.records.extend(records);
+        if self.records.len() > MAX_RECORDS {
+            self.records.drain(..self.records.len() - MAX_RECORDS);
+        }
+        self.clamp();
+    }
+
+    fn visible(&self) -> Vec<&LogRecord> {
+        self.records.iter().filter(|record| record.level >= self.min_level).collect()
+    }
+
+    fn clamp(&mut self) {
+        let last = self.visible().len().saturating_sub(1);
+        self.selected = if self.follow { last } else { self.selected.min(last) };
+    }
+
+    fn select_next(&mut self) {
+        self.selected += 1;
+        self.clamp();
+    }
+
+    fn select_prev(&mut self) {
+        self.follow = false;
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    fn line<'a>(record: &'a LogRecord, theme: &Theme) -> Line<'a> {
+        let style = match record.level {
+            Level::Error => theme.error,
+            Level::Warn => theme.warning,
+            Level::Info => theme.text,
+            Level::Debug => theme.muted,
+        };
+        let time = record.time.get(11..19).unwrap_or(&record.time);
+        let fields: Vec<String> = record
+            .fields
+            .iter()
+            .map(|(name, value)| match value {
+                Value::String(text) => format!("{}={}", name, text),
+                value => format!("{}={}", name, value),
+            })
+            .collect();
+        Line::from(vec![
+            Span::styled(format!("{} ", time), theme.muted),
+            Span::styled(format!("{:<5} ", record.level.label()), style),
+            Span::styled(format!("{:<5} ", record.target), theme.accent),
+            Span::styled(record.message.as_str(), style),
+            Span::styled(if fields.is_empty() { String::new() } else { format!("  {}", fields.join(" ")) }, theme.muted),
+        ])
+    }
+}
+
+impl View for LogView {
+    fn title(&self) -> &'static str {
+        "Log View"
+    }
+
+    fn shortcut(&self) -> char {
+        '5'
+    }
+
+    fn hints(&self) -> &'static [(&'static str, &'static str)] {
+        &[("select_next", "Next"), ("select_prev", "Previous"), ("cycle_level", "Level"), ("toggle_follow", "Follow")]
+    }
+
+    fn keymap_scope(&self) -> &'static str {
+        "log"
+    }
+
+    fn render(&self, f: &mut Frame, area: Rect, theme: &Theme) {
+        self.area.set(area);
+        let visible = self.visible();
+        let title = format!(
+            "Log ({} of {}) level: {} follow: {}",
+            visible.len(),
+            self.records.len(),
+            self.min_level.label(),
+            if self.follow { "on" } else { "off" }
+        );
+        let items: Vec<ListItem> = visible.iter().map(|record| ListItem::new(Self::line(record, theme))).collect();
+        let list = List::new(items).block(theme.block(title)).highlight_style(theme.highlight);
+        let selected = if visible.is_empty() { None } else { Some(self.selected) };
+        f.render_stateful_widget(list, area, &mut ListState::default().with_selected(selected));
+    }
+
+    fn handle_input(&mut self, _key: KeyEvent, binding: Option<&str>, notifications: &mut Notifications) -> Action {
+        match binding {
+            Some("select_next") => self.select_next(),
+            Some("select_prev") => self.select_prev(),
+            Some("select_first") => {
+                self.follow = false;
+                self.selected = 0;
+            }
+            Some("select_last") => {
+                self.follow = true;
+                self.clamp();
+            }
+            Some("cycle_level") => {
+                self.min_level = self.min_level.next();
+                self.clamp();
+                notifications.info(format!("Log level: {}", self.min_level.label()));
+            }
+            Some("toggle_follow") => {
+                self.follow = !self.follow;
+                self.clamp();
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn handle_mouse(&mut self, event: MouseEvent, _notifications: &mut Notifications) -> Action {
+        match event.kind {
+            MouseEventKind::ScrollDown if mouse::hit(self.area.get(), &event) => self.select_next(),
+            MouseEventKind::ScrollUp if mouse::hit(self.area.get(), &event) => self.select_prev(),
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn tick(&mut self) -> Action {
+        let records = logging::since(self.last_seq);
+        if !records.is_empty() {
+            self.append(records);
+        }
+        Action::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LogView;
+    use crate::logging::{Level, LogRecord};
+    use crate::snapshot::{key, Harness};
+    use crossterm::event::KeyCode;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    fn record(seq: u64, level: Level, target: &str, message: &str) -> LogRecord {
+        let mut fields = BTreeMap::new();
+        if target == "http" {
+            fields.insert("status".to_string(), json!(200));
+            fields.insert("url".to_string(), json!("https://api.example.com/v1"));
+        }
+        LogRecord {
+            seq,
+            time: format!("2024-05-01T12:00:0{}+00:00", seq),
+            level,
+            target: target.to_string(),
+            message: message.to_string(),
+            fields,
+        }
+    }
+
+    fn view() -> LogView {
+        let mut view = LogView::new();
+        view.append(vec![
+            record(1, Level::Debug, "chat", "resolving providers"),
+            record(2, Level::Info, "http", "request completed"),
+            record(3, Level::Warn, "chat", "openai retry #1 quota after 1.0s"),
+            record(4, Level::Error, "chat", "openai failed: quota"),
+        ]);
+        view
+    }
+
+    #[test]
+    fn filters_by_level_and_follows_new_records() {
+        let mut harness = Harness::new(view(), 80, 7);
+        harness
+            .assert_snapshot("log_view_follow")
+            .keys(&[key(KeyCode::Char('l'))])
+            .assert_snapshot("log_view_warn")
+            .keys(&[key(KeyCode::Char('l')), key(KeyCode::Char('l'))])
+            .assert_snapshot("log_view_debug");
+        assert_eq!(harness.notifications.latest().unwrap().message, "Log level: DEBUG");
+
+        harness.keys(&[key(KeyCode::Up), key(KeyCode::Up)]);
+        harness.subject.append(vec![record(5, Level::Info, "http", "request completed")]);
+        assert!(!harness.subject.follow);
+        assert_eq!(harness.subject.selected, 1);
+
+        harness.keys(&[key(KeyCode::Char('f'))]);
+        assert_eq!(harness.subject.selected, 4);
+        harness.subject.append(vec![record(6, Level::Error, "http", "connection refused")]);
+        assert_eq!(harness.subject.selected, 5);
+        assert_eq!(harness.subject.last_seq, 6);
+    }
+}
@@ -0,0 +1,323 @@
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, TryLockError};
+
+pub const REDACTED: &str = "[REDACTED]";
+const SENSITIVE_SEGMENTS: [&str; 8] = ["auth", "authorization", "apikey", "token", "secret", "cookie", "password", "passphrase"];
+const BUFFER_LEN: usize = 2000;
+const MAX_BYTES: u64 = 1024 * 1024;
+const KEEP_FILES: usize = 3;
+
+static LOGGER: LazyLock<Mutex<Logger>> = LazyLock::new(|| Mutex::new(Logger::new(None, Level::Info)));
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Level::Debug => "DEBUG",
+            Level::Info => "INFO",
+            Level::Warn => "WARN",
+            Level::Error => "ERROR",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "debug" => Some(Level::Debug),
+            "info" => Some(Level::Info),
+            "warn" | "warning" => Some(Level::Warn),
+            "error" => Some(Level::Error),
+            _ => None,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Level::Debug => Level::Info,
+            Level::Info => Level::Warn,
+            Level::Warn => Level::Error,
+            Level::Error => Level::Debug,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LogRecord {
+    #[serde(skip)]
+    pub seq: u64,
+    pub time: String,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, Value>,
+}
+
+pub struct Logger {
+    path: Option<PathBuf>,
+    level: Level,
+    max_bytes: u64,
+    keep: usize,
+    secrets: Vec<String>,
+    buffer: VecDeque<LogRecord>,
+    next_seq: u64,
+}
+
+impl Logger {
+    pub fn new(path: Option<PathBuf>, level: Level) -> Self {
+        Self { path, level, max_bytes: MAX_BYTES, keep: KEEP_FILES, secrets: Vec::new(), buffer: VecDeque::new(), next_seq: 1 }
+    }
+
+    pub fn add_secret(&mut self, secret: &str) {
+        let secret = secret.trim();
+        if secret.len() >= 4 && !self.secrets.iter().any(|known| known == secret) {
+            self.secrets.push(secret.to_string());
+        }
+    }
+
+    pub fn scrub(&self, text: &str) -> String {
+        self.secrets.iter().fold(text.to_string(), |text, secret| text.replace(secret.as_str(), REDACTED))
+    }
+
+    fn redact(&self, name: &str, value: Value) -> Value {
+        if is_sensitive(name) {
+            return Value::String(REDACTED.to_string());
+        }
+        match value {
+            Value::String(text) => Value::String(self.scrub(&text)),
+            Value::Array(items) => Value::Array(items.into_iter().map(|item| self.redact("", item)).collect()),
+            Value::Object(map) => Value::Object(map.into_iter().map(|(key, item)| {
+                let item = self.redact(&key, item);
+                (key, item)
+            }).collect()),
+            value => value,
+        }
+    }
+
+    pub fn log(&mut self, level: Level, target: &str, message: &str, fields: Vec<(&str, Value)>) {
+        if level < self.level {
+            return;
+        }
+        let record = LogRecord {
+            seq: self.next_seq,
+            time: Local::now().to_rfc3339(),
+            level,
+            target: target.to_string(),
+            message: self.scrub(message),
+            fields: fields.into_iter().map(|(name, value)| (name.to_string(), self.redact(name, value))).collect(),
+        };
+        self.next_seq += 1;
+        if let Some(path) = &self.path {
+            let _ = Self::append(path, &record, self.max_bytes, self.keep);
+        }
+        if self.buffer.len() == BUFFER_LEN {
+            self.buffer.pop_front();
+        }
+        self.buffer.push_back(record);
+    }
+
+    fn append(path: &Path, record: &LogRecord, max_bytes: u64, keep: usize) -> io::Result<()> {
+        let line = serde_json::to_string(record).map_err(io::Error::other)?;
+        if fs::metadata(path).is_ok_and(|metadata| metadata.len() + line.len() as u64 + 1 > max_bytes) {
+            rotate(path, keep)?;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", line)
+    }
+
+    pub fn since(&self, seq: u64) -> Vec<LogRecord> {
+        self.buffer.iter().filter(|record| record.seq > seq).cloned().collect()
+    }
+}
+
+fn rotated(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+fn rotate(path: &Path, keep: usize) -> io::Result<()> {
+    let _ = fs::remove_file(rotated(path, keep));
+    for index in (1..keep).rev() {
+        let _ = fs::rename(rotated(path, index), rotated(path, index + 1));
+    }
+    fs::rename(path, rotated(path, 1))
+}
+
+pub fn is_sensitive(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    let segments: Vec<&str> = name.split(['-', '_', '.', ' ']).collect();
+    segments.iter().any(|segment| SENSITIVE_SEGMENTS.contains(segment))
+        || segments.windows(2).any(|pair| pair == ["api", "key"])
+        || name == "key"
+}
+
+pub fn header_secrets(headers: &[(String, String)]) -> Vec<String> {
+    headers
+        .iter()
+        .filter(|(name, _)| is_sensitive(name))
+        .flat_map(|(_, value)| {
+            let value = value.trim();
+            let token = value.split_once(' ').map(|(_, token)| token.trim()).unwrap_or(value);
+            [value.to_string(), token.to_string()]
+        })
+        .filter(|secret| secret.len() >= 4)
+        .collect()
+}
+
+pub fn init(path: PathBuf, level: Level) {
+    if let Ok(mut logger) = LOGGER.lock() {
+        logger.path = Some(path);
+        logger.level = level;
+    }
+}
+
+pub fn add_secrets(secrets: &[String]) {
+    if let Ok(mut logger) = LOGGER.lock() {
+        for secret in secrets {
+            logger.add_secret(secret);
+        }
+    }
+}
+
+pub fn log(level: Level, target: &str, message: &str, fields: Vec<(&str, Value)>) {
+    if let Ok(mut logger) = LOGGER.lock() {
+        logger.log(level, target, message, fields);
+    }
+}
+
+pub fn debug(target: &str, message: &str, fields: Vec<(&str, Value)>) {
+    log(Level::Debug, target, message, fields);
+}
+
+pub fn info(target: &str, message: &str, fields: Vec<(&str, Value)>) {
+    log(Level::Info, target, message, fields);
+}
+
+pub fn warn(target: &str, message: &str, fields: Vec<(&str, Value)>) {
+    log(Level::Warn, target, message, fields);
+}
+
+pub fn error(target: &str, message: &str, fields: Vec<(&str, Value)>) {
+    log(Level::Error, target, message, fields);
+}
+
+/// Logs an error without waiting for the logger, for the panic hook: the
+/// panicking thread may already hold it, so the record is dropped instead.
+pub fn try_error(target: &str, message: &str, fields: Vec<(&str, Value)>) {
+    let mut logger = match LOGGER.try_lock() {
+        Ok(logger) => logger,
+        Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+        Err(TryLockError::WouldBlock) => return,
+    };
+    logger.log(Level::Error, target, message, fields);
+}
+
+pub fn since(seq: u64) -> Vec<LogRecord> {
+    LOGGER.lock().map(|logger| logger.since(seq)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{header_secrets, is_sensitive, try_error, Level, Logger, LogRecord, LOGGER, REDACTED};
+    use crate::snapshot;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn redacts_sensitive_fields_and_known_secrets() {
+        let mut logger = Logger::new(None, Level::Info);
+        for secret in header_secrets(&[("Authorization".to_string(), "Bearer sk-live-123456".to_string())]) {
+            logger.add_secret(&secret);
+        }
+        logger.log(
+            Level::Info,
+            "http",
+            "sent with sk-live-123456",
+            vec![
+                ("url", json!("https://api.example.com/v1?key=sk-live-123456")),
+                ("api_key", json!("anything")),
+                ("headers", json!({"x-api-key": "abc", "accept": "json"})),
+                ("status", json!(200)),
+            ],
+        );
+        logger.log(Level::Debug, "http", "dropped below the level", Vec::new());
+
+        let records = logger.since(0);
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.message, format!("sent with {}", REDACTED));
+        assert_eq!(record.fields["url"], json!(format!("https://api.example.com/v1?key={}", REDACTED)));
+        assert_eq!(record.fields["api_key"], json!(REDACTED));
+        assert_eq!(record.fields["headers"], json!({"x-api-key": REDACTED, "accept": "json"}));
+        assert_eq!(record.fields["status"], json!(200));
+        assert!(logger.since(record.seq).is_empty());
+    }
+
+    #[test]
+    fn keeps_usage_counts_and_rate_limit_headers() {
+        for name in ["Authorization", "Proxy-Authorization", "x-api-key", "api-key", "x-goog-api-key", "api_key", "Set-Cookie", "X-Auth-Token", "access_token", "client_secret", "password"] {
+            assert!(is_sensitive(name), "{} should be redacted", name);
+        }
+        for name in ["prompt_tokens", "completion_tokens", "total_tokens", "x-ratelimit-remaining-tokens", "keymap", "monkey", "author"] {
+            assert!(!is_sensitive(name), "{} should be kept", name);
+        }
+
+        let mut logger = Logger::new(None, Level::Info);
+        logger.log(
+            Level::Info,
+            "chat",
+            "completion finished",
+            vec![("usage", json!({"prompt_tokens": 12, "completion_tokens": 30, "total_tokens": 42}))],
+        );
+        assert_eq!(logger.since(0)[0].fields["usage"], json!({"prompt_tokens": 12, "completion_tokens": 30, "total_tokens": 42}));
+    }
+
+    #[test]
+    fn rotates_log_files() {
+        let path = snapshot::temp_dir("logging").join("logs").join("ai.log");
+        let mut logger = Logger::new(Some(path.clone()), Level::Debug);
+        logger.max_bytes = 300;
+        logger.keep = 2;
+        for index in 0..12 {
+            logger.log(Level::Warn, "test", &format!("message {}", index), vec![("index", json!(index))]);
+        }
+
+        let current = fs::read_to_string(&path).unwrap();
+        let last: LogRecord = serde_json::from_str(current.lines().last().unwrap()).unwrap();
+        assert_eq!(last.message, "message 11");
+        assert_eq!(last.level, Level::Warn);
+        assert!(current.len() <= 300);
+        assert!(path.with_file_name("ai.log.1").exists());
+        assert!(path.with_file_name("ai.log.2").exists());
+        assert!(!path.with_file_name("ai.log.3").exists());
+    }
+
+    #[test]
+    fn skips_panic_records_while_the_logger_is_held() {
+        let held = LOGGER.lock().unwrap();
+        let seq = held.since(0).last().map_or(0, |record| record.seq);
+        try_error("panic", "boom", Vec::new());
+        drop(held);
+        try_error("panic", "boom again", Vec::new());
+        let messages: Vec<_> = super::since(seq).into_iter().map(|record| record.message).collect();
+        assert!(messages.contains(&"boom again".to_string()));
+        assert!(!messages.contains(&"boom".to_string()));
+    }
+}
@@ -1,28 +1,65 @@
 mod app;
+mod cassette;
+mod chat;
+mod chat_view;
+mod clipboard;
+mod config;
 mod context_view;
+mod editor;
+mod env;
+mod fuzzy;
+mod headless;
+mod help;
+mod http;
+mod keymap;
+mod layout;
+mod log_view;
+mod logging;
+mod mock;
+mod mouse;
+mod notify;
+mod palette;
+mod provider;
 mod provider_view;
+mod registry;
+mod retry;
+mod routing;
+mod secret;
+#[cfg(test)]
+mod snapshot;
+mod store;
+mod terminal;
+mod theme;
 mod traits;
+mod usage;
+mod usage_view;
+mod widgets;
 
 use crate::app::App;
-use crossterm::{
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
-use ratatui::backend::CrosstermBackend;
-use ratatui::Terminal;
 use std::io;
 
 fn main() -> Result<(), io::Error> {
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let terminal = Terminal::new(backend)?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let level = std::env::var("AI_LOG").ok().and_then(|level| logging::Level::parse(&level)).unwrap_or(logging::Level::Info);
+    logging::init(config::log_path(), level);
+    if let Err(e) = cassette::start_from_args(&args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    if let Some(options) = headless::Options::parse(&args) {
+        if let Err(e) = options.and_then(headless::run) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let signals = terminal::Signals::register()?;
+    let terminal = terminal::init()?;
 
     let mut app = App::new();
-    let result = app.run(terminal);
+    let result = app.run(terminal, &signals);
 
-    disable_raw_mode()?;
-    execute!(io::stdout(), LeaveAlternateScreen)?;
+    terminal::restore();
     result
 }
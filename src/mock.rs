@@ -0,0 +1,226 @@
+use crate::chat::{ChatMessage, ChatResponse, Role, ToolCall};
+use crate::provider::{ConnectionStatus, ErrorKind, ProviderError};
+use crate::routing;
+use crate::usage::Usage;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+static CURSORS: LazyLock<Mutex<HashMap<PathBuf, usize>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Fixture {
+    pub model: Option<String>,
+    pub responses: Vec<MockResponse>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MockResponse {
+    #[serde(rename = "match")]
+    pub matches: Option<String>,
+    pub content: String,
+    pub chunks: Vec<String>,
+    pub chunk_delay_ms: u64,
+    pub delay_ms: u64,
+    pub tool_calls: Vec<ToolCall>,
+    pub usage: Option<Usage>,
+    pub model: Option<String>,
+    pub error: Option<MockError>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct MockError {
+    pub status: Option<u16>,
+    pub message: String,
+    pub retry_after_secs: Option<f64>,
+}
+
+impl Fixture {
+    pub fn load(path: &Path) -> Result<Self, ProviderError> {
+        if path.as_os_str().is_empty() {
+            return Err(ProviderError::new(ErrorKind::WrongUrl, None, "no fixture file configured".to_string()));
+        }
+        let content = fs::read_to_string(path).map_err(|e| {
+            ProviderError::new(ErrorKind::WrongUrl, None, format!("cannot read fixture {}: {}", path.display(), e))
+        })?;
+        serde_yaml::from_str(&content).map_err(|e| {
+            ProviderError::new(ErrorKind::Other, None, format!("invalid fixture {}: {}", path.display(), e))
+        })
+    }
+
+    fn next(&self, path: &Path, prompt: &str) -> Option<MockResponse> {
+        if let Some(response) = self
+            .responses
+            .iter()
+            .find(|response| response.matches.as_deref().is_some_and(|pattern| prompt.contains(pattern)))
+        {
+            return Some(response.clone());
+        }
+        let scripted: Vec<&MockResponse> = self.responses.iter().filter(|response| response.matches.is_none()).collect();
+        if scripted.is_empty() {
+            return None;
+        }
+        let mut cursors = CURSORS.lock().unwrap();
+        let cursor = cursors.entry(path.to_path_buf()).or_default();
+        let response = scripted[*cursor % scripted.len()].clone();
+        *cursor += 1;
+        Some(response)
+    }
+}
+
+pub fn send(path: &Path, model: &str, messages: &[ChatMessage], on_chunk: &mut dyn FnMut(&str)) -> Result<ChatResponse, ProviderError> {
+    let fixture = Fixture::load(path)?;
+    let prompt = messages
+        .iter()
+        .rev()
+        .find(|message| message.role == Role::User)
+        .map(|message| message.content.as_str())
+        .unwrap_or_default();
+    let response = fixture
+        .next(path, prompt)
+        .ok_or_else(|| ProviderError::new(ErrorKind::Other, None, format!("no response in {} matches the prompt", path.display())))?;
+
+    thread::sleep(Duration::from_millis(response.delay_ms));
+    if let Some(error) = response.error {
+        let kind = error.status.and_then(ErrorKind::from_status).unwrap_or(ErrorKind::Connection);
+        return Err(ProviderError {
+            retry_after: error.retry_after_secs.map(|secs| Duration::from_secs_f64(secs.max(0.0))),
+            ..ProviderError::new(kind, error.status, error.message)
+        });
+    }
+
+    let content = if response.chunks.is_empty() {
+        response.content
+    } else {
+        for (index, chunk) in response.chunks.iter().enumerate() {
+            if index > 0 {
+                thread::sleep(Duration::from_millis(response.chunk_delay_ms));
+            }
+            on_chunk(chunk);
+        }
+        response.chunks.concat()
+    };
+    let usage = response.usage.unwrap_or_else(|| Usage {
+        prompt_tokens: messages.iter().map(|message| routing::estimate_tokens(&message.content)).sum(),
+        completion_tokens: routing::estimate_tokens(&content),
+    });
+    Ok(ChatResponse {
+        content,
+        model: response.model.or(fixture.model).unwrap_or_else(|| model.to_string()),
+        usage,
+        tool_calls: response.tool_calls,
+    })
+}
+
+pub fn check(path: &Path) -> ConnectionStatus {
+    let started = Instant::now();
+    match Fixture::load(path) {
+        Ok(_) => ConnectionStatus::Ok { latency: started.elapsed(), status: 200 },
+        Err(e) => ConnectionStatus::Failed { latency: started.elapsed(), status: None, kind: e.kind, detail: e.message },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, send};
+    use crate::chat::{ChatMessage, Role};
+    use crate::provider::{ConnectionStatus, ErrorKind};
+    use crate::snapshot;
+    use crate::usage::Usage;
+    use serde_json::json;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    const FIXTURE: &str = r#"
+model: mock-large
+responses:
+  - match: weather
+    content: Sunny
+    usage: { prompt_tokens: 7, completion_tokens: 1 }
+  - match: tool
+    content: ""
+    tool_calls:
+      - name: lookup
+        arguments: { city: Paris }
+  - match: limit
+    error: { status: 429, message: slow down, retry_after_secs: 2.5 }
+  - match: offline
+    error: { message: connection reset }
+  - chunks: ["first ", "answer"]
+  - content: second answer
+    model: mock-small
+"#;
+
+    fn fixture(name: &str, content: &str) -> PathBuf {
+        let path = snapshot::temp_dir(name).join("fixture.yaml");
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn ask(path: &Path, prompt: &str) -> Result<(String, String), String> {
+        let messages = [ChatMessage { role: Role::User, content: prompt.to_string() }];
+        let mut streamed = String::new();
+        send(path, "configured", &messages, &mut |chunk| streamed.push_str(chunk))
+            .map(|response| (response.content, format!("{} {}", response.model, streamed)))
+            .map_err(|e| e.message)
+    }
+
+    #[test]
+    fn answers_matching_entries_before_cycling_scripted_ones() {
+        let path = fixture("mock-script", FIXTURE);
+        let messages = [ChatMessage { role: Role::User, content: "what is the weather".to_string() }];
+        let response = send(&path, "configured", &messages, &mut |_| {}).unwrap();
+        assert_eq!(response.content, "Sunny");
+        assert_eq!(response.model, "mock-large");
+        assert_eq!(response.usage, Usage { prompt_tokens: 7, completion_tokens: 1 });
+
+        assert_eq!(ask(&path, "hello"), Ok(("first answer".to_string(), "mock-large first answer".to_string())));
+        assert_eq!(ask(&path, "hello"), Ok(("second answer".to_string(), "mock-small ".to_string())));
+        assert_eq!(ask(&path, "hello"), Ok(("first answer".to_string(), "mock-large first answer".to_string())));
+    }
+
+    #[test]
+    fn returns_tool_calls_and_scripted_errors() {
+        let path = fixture("mock-errors", FIXTURE);
+        let messages = [ChatMessage { role: Role::User, content: "use a tool".to_string() }];
+        let response = send(&path, "configured", &messages, &mut |_| {}).unwrap();
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].name, "lookup");
+        assert_eq!(response.tool_calls[0].arguments, json!({ "city": "Paris" }));
+
+        let messages = [ChatMessage { role: Role::User, content: "hit the limit".to_string() }];
+        let error = send(&path, "configured", &messages, &mut |_| {}).unwrap_err();
+        assert_eq!((error.kind, error.status, error.message.as_str()), (ErrorKind::Quota, Some(429), "slow down"));
+        assert_eq!(error.retry_after, Some(Duration::from_millis(2500)));
+
+        let messages = [ChatMessage { role: Role::User, content: "go offline".to_string() }];
+        let error = send(&path, "configured", &messages, &mut |_| {}).unwrap_err();
+        assert_eq!((error.kind, error.status), (ErrorKind::Connection, None));
+    }
+
+    #[test]
+    fn reports_missing_invalid_and_exhausted_fixtures() {
+        let dir = snapshot::temp_dir("mock-invalid");
+        let missing = dir.join("missing.yaml");
+        let error = ask(&missing, "hello").unwrap_err();
+        assert!(error.starts_with("cannot read fixture"), "{}", error);
+        assert!(matches!(check(&missing), ConnectionStatus::Failed { kind: ErrorKind::WrongUrl, .. }));
+        assert_eq!(ask(Path::new(""), "hello"), Err("no fixture file configured".to_string()));
+
+        let invalid = fixture("mock-invalid-yaml", "responses: [unterminated");
+        assert!(ask(&invalid, "hello").unwrap_err().starts_with("invalid fixture"));
+        assert!(matches!(check(&invalid), ConnectionStatus::Failed { kind: ErrorKind::Other, .. }));
+
+        let matches_only = fixture("mock-matches-only", "responses:\n  - match: weather\n    content: Sunny\n");
+        assert!(matches!(check(&matches_only), ConnectionStatus::Ok { status: 200, .. }));
+        assert!(ask(&matches_only, "hello").unwrap_err().starts_with("no response in"));
+    }
+}
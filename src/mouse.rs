@@ -0,0 +1,19 @@
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+
+pub fn hit(area: Rect, event: &MouseEvent) -> bool {
+    area.contains(Position { x: event.column, y: event.row })
+}
+
+pub fn clicked(area: Rect, event: &MouseEvent) -> bool {
+    matches!(event.kind, MouseEventKind::Down(MouseButton::Left)) && hit(area, event)
+}
+
+pub fn list_row(area: Rect, offset: usize, len: usize, event: &MouseEvent) -> Option<usize> {
+    let inner = Rect { x: area.x + 1, y: area.y + 1, width: area.width.saturating_sub(2), height: area.height.saturating_sub(2) };
+    if !clicked(inner, event) {
+        return None;
+    }
+    let index = offset + (event.row - inner.y) as usize;
+    (index < len).then_some(index)
+}
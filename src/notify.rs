@@ -0,0 +1,164 @@
+use crate::logging;
+use crate::theme::Theme;
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    widgets::{Clear, Paragraph, Wrap},
+    Frame,
+};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const MAX_QUEUED: usize = 20;
+const MAX_TOASTS: usize = 3;
+const TOAST_WIDTH: u16 = 48;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Info => "Info",
+            Severity::Success => "Success",
+            Severity::Warning => "Warning",
+            Severity::Error => "Error",
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        match self {
+            Severity::Info | Severity::Success => Duration::from_secs(4),
+            Severity::Warning => Duration::from_secs(8),
+            Severity::Error => Duration::from_secs(12),
+        }
+    }
+
+    pub fn style(&self, theme: &Theme) -> Style {
+        match self {
+            Severity::Info => theme.info,
+            Severity::Success => theme.success,
+            Severity::Warning => theme.warning,
+            Severity::Error => theme.error,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Notification {
+    pub severity: Severity,
+    pub message: String,
+    expires: Instant,
+}
+
+#[derive(Default)]
+pub struct Notifications {
+    queue: VecDeque<Notification>,
+}
+
+impl Notifications {
+    pub fn push(&mut self, severity: Severity, message: impl Into<String>) {
+        let message = message.into();
+        if message.is_empty() {
+            return;
+        }
+        match severity {
+            Severity::Warning => logging::warn("notify", &message, Vec::new()),
+            Severity::Error => logging::error("notify", &message, Vec::new()),
+            _ => {}
+        }
+        self.queue.retain(|notification| notification.message != message);
+        if self.queue.len() == MAX_QUEUED {
+            self.queue.pop_front();
+        }
+        self.queue.push_back(Notification { severity, message, expires: Instant::now() + severity.timeout() });
+    }
+
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(Severity::Info, message);
+    }
+
+    pub fn success(&mut self, message: impl Into<String>) {
+        self.push(Severity::Success, message);
+    }
+
+    pub fn warning(&mut self, message: impl Into<String>) {
+        self.push(Severity::Warning, message);
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(Severity::Error, message);
+    }
+
+    pub fn latest(&self) -> Option<&Notification> {
+        self.queue.back()
+    }
+
+    pub fn count(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn dismiss(&mut self) {
+        self.queue.pop_back();
+    }
+
+    pub fn expire(&mut self, now: Instant) {
+        self.queue.retain(|notification| notification.expires > now);
+    }
+
+    pub fn render(&self, f: &mut Frame, area: Rect, theme: &Theme) {
+        let width = TOAST_WIDTH.min(area.width);
+        let inner_width = width.saturating_sub(2).max(1) as usize;
+        let mut bottom = area.bottom();
+        for notification in self.queue.iter().rev().take(MAX_TOASTS) {
+            let lines = notification.message.chars().count().div_ceil(inner_width).clamp(1, 3) as u16;
+            let height = lines + 2;
+            if bottom < area.y + height {
+                break;
+            }
+            bottom -= height;
+            let toast = Rect { x: area.right() - width, y: bottom, width, height };
+            let style = notification.severity.style(theme);
+            f.render_widget(Clear, toast);
+            f.render_widget(
+                Paragraph::new(notification.message.as_str())
+                    .style(theme.text)
+                    .wrap(Wrap { trim: true })
+                    .block(theme.block(notification.severity.label()).border_style(style).title_style(style)),
+                toast,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Notifications, Severity};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn stacks_and_expires_by_severity() {
+        let mut notifications = Notifications::default();
+        notifications.info("Theme: light");
+        notifications.error("openai failed: quota");
+        notifications.warning("openai retry #1 quota after 1.0s");
+        notifications.info("Theme: light");
+        notifications.push(Severity::Success, "");
+        assert_eq!(notifications.count(), 3);
+        assert_eq!(notifications.latest().unwrap().message, "Theme: light");
+
+        notifications.expire(Instant::now() + Duration::from_secs(5));
+        assert_eq!(notifications.count(), 2);
+        assert_eq!(notifications.latest().unwrap().severity, Severity::Warning);
+
+        notifications.dismiss();
+        assert_eq!(notifications.latest().unwrap().severity, Severity::Error);
+        notifications.expire(Instant::now() + Duration::from_secs(13));
+        assert_eq!(notifications.count(), 0);
+    }
+}
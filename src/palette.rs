@@ -0,0 +1,137 @@
+use crate::fuzzy::fuzzy_match;
+use crate::theme::Theme;
+use crate::widgets::TextInput;
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Target {
+    Global(String),
+    Action { view: usize, action: String },
+    Command { view: usize, command: String },
+}
+
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub label: String,
+    pub hint: String,
+    pub target: Target,
+}
+
+pub enum Outcome {
+    Open,
+    Closed,
+    Run(Target),
+}
+
+pub struct Palette {
+    query: TextInput,
+    entries: Vec<Entry>,
+    matches: Vec<(usize, Vec<usize>)>,
+    selected: usize,
+}
+
+impl Palette {
+    pub fn new(entries: Vec<Entry>) -> Self {
+        let mut palette = Self { query: TextInput::new(), entries, matches: Vec::new(), selected: 0 };
+        palette.filter();
+        palette
+    }
+
+    fn filter(&mut self) {
+        let mut ranked: Vec<(i64, usize, Vec<usize>)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| fuzzy_match(self.query.value(), &entry.label).map(|found| (found.score, index, found.positions)))
+            .collect();
+        if !self.query.value().is_empty() {
+            ranked.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        }
+        self.matches = ranked.into_iter().map(|(_, index, positions)| (index, positions)).collect();
+        self.selected = 0;
+    }
+
+    pub fn handle_input(&mut self, key: KeyEvent, binding: Option<&str>) -> Outcome {
+        match (binding, key.code) {
+            (Some("select_next"), _) => self.selected = (self.selected + 1).min(self.matches.len().saturating_sub(1)),
+            (Some("select_prev"), _) => self.selected = self.selected.saturating_sub(1),
+            (Some("confirm"), _) => {
+                return match self.matches.get(self.selected) {
+                    Some((index, _)) => Outcome::Run(self.entries[*index].target.clone()),
+                    None => Outcome::Closed,
+                };
+            }
+            (Some("cancel"), _) => return Outcome::Closed,
+            _ => {
+                let query = self.query.value().to_string();
+                if self.query.handle_input(key, binding) && self.query.value() != query {
+                    self.filter();
+                }
+            }
+        }
+        Outcome::Open
+    }
+
+    pub fn handle_paste(&mut self, text: &str) {
+        self.query.insert_str(text);
+        self.filter();
+    }
+
+    pub fn render(&self, f: &mut Frame, area: Rect, theme: &Theme) {
+        let width = (area.width * 3 / 5).max(40).min(area.width);
+        let height = (self.matches.len() as u16 + 4).clamp(6, 16).min(area.height);
+        let popup = centered(area, width, height);
+        f.render_widget(Clear, popup);
+        let block = theme.block(format!("Command Palette ({}/{})", self.matches.len(), self.entries.len()));
+        let inner = block.inner(popup);
+        f.render_widget(block, popup);
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Length(1), Constraint::Min(0)])
+            .split(inner);
+        f.render_widget(Paragraph::new("> ").style(theme.text), rows[0]);
+        self.query.render(f, Rect { x: rows[0].x + 2, width: rows[0].width.saturating_sub(2), ..rows[0] }, theme.text, true);
+        f.render_widget(Paragraph::new("─".repeat(rows[1].width as usize)).style(theme.border), rows[1]);
+
+        let items: Vec<ListItem> = self
+            .matches
+            .iter()
+            .map(|(index, positions)| {
+                let entry = &self.entries[*index];
+                let mut spans: Vec<Span> = entry
+                    .label
+                    .chars()
+                    .enumerate()
+                    .map(|(position, c)| {
+                        Span::styled(c.to_string(), if positions.contains(&position) { theme.highlight } else { theme.text })
+                    })
+                    .collect();
+                if !entry.hint.is_empty() {
+                    spans.push(Span::styled(format!("  {}", entry.hint), theme.muted));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items).highlight_style(theme.header_active).highlight_symbol("> ");
+        f.render_stateful_widget(list, rows[2], &mut ListState::default().with_selected(Some(self.selected)));
+    }
+}
+
+pub fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect { x: area.x + (area.width - width) / 2, y: area.y + (area.height - height) / 3, width, height }
+}
+
+pub fn humanize(action: &str) -> String {
+    let words = action.replace('_', " ");
+    let mut chars = words.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
@@ -0,0 +1,398 @@
+use crate::env::{self, Env};
+use crate::http::{self, HttpRequest, NetworkSettings, ResolvedNetwork};
+use crate::mock;
+use crate::retry::{RateLimit, RetryPolicy};
+use crate::secret::{self, Resolved, Vault};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug)]
+pub struct ProviderInstance {
+    pub name: String,
+    pub provider_type: ProviderType,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ProviderType {
+    OpenAI,
+    Ollama,
+    AzureOpenAI,
+    Gemini,
+    Grog,
+    Claude,
+    OpenAICompatible,
+    Mock,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ProviderSettings {
+    pub api_key: Option<String>,
+    pub api_entry_point: Option<String>,
+    pub api_deployment: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub auth_header: Option<String>,
+    #[serde(default)]
+    pub models: Vec<String>,
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    #[serde(default)]
+    pub rate_limit: RateLimit,
+    #[serde(default)]
+    pub network: NetworkSettings,
+}
+
+impl fmt::Debug for ProviderSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProviderSettings")
+            .field("api_key", &self.api_key.as_deref().map(secret::mask))
+            .field("api_entry_point", &self.api_entry_point)
+            .field("api_deployment", &self.api_deployment)
+            .field("model", &self.model)
+            .field("auth_header", &self.auth_header)
+            .field("models", &self.models)
+            .field("retry", &self.retry)
+            .field("rate_limit", &self.rate_limit)
+            .field("proxy", &self.network.proxy)
+            .field("no_proxy", &self.network.no_proxy)
+            .field("ca_bundle", &self.network.ca_bundle)
+            .field("timeout_secs", &self.network.timeout_secs)
+            .field("headers", &self.network.headers.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum AuthStyle {
+    #[default]
+    Bearer,
+    Header(String),
+    None,
+}
+
+impl AuthStyle {
+    pub fn parse(value: &str) -> Self {
+        match value.trim() {
+            "" => AuthStyle::Bearer,
+            value if value.eq_ignore_ascii_case("bearer") => AuthStyle::Bearer,
+            value if value.eq_ignore_ascii_case("none") => AuthStyle::None,
+            value => AuthStyle::Header(value.to_string()),
+        }
+    }
+
+    pub fn apply(&self, request: HttpRequest, key: &str) -> HttpRequest {
+        match self {
+            AuthStyle::Bearer if !key.is_empty() => request.header("Authorization", format!("Bearer {}", key)),
+            AuthStyle::Header(name) => request.header(name.as_str(), key),
+            _ => request,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    BadKey,
+    WrongUrl,
+    Dns,
+    Tls,
+    Quota,
+    Timeout,
+    Connection,
+    Server,
+    Other,
+}
+
+impl ErrorKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ErrorKind::BadKey => "bad key",
+            ErrorKind::WrongUrl => "wrong URL",
+            ErrorKind::Dns => "DNS",
+            ErrorKind::Tls => "TLS",
+            ErrorKind::Quota => "quota",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::Connection => "connection",
+            ErrorKind::Server => "server",
+            ErrorKind::Other => "error",
+        }
+    }
+
+    pub fn from_status(status: u16) -> Option<Self> {
+        match status {
+            200..=399 => None,
+            401 | 403 => Some(ErrorKind::BadKey),
+            404 | 405 => Some(ErrorKind::WrongUrl),
+            402 | 429 => Some(ErrorKind::Quota),
+            500..=599 => Some(ErrorKind::Server),
+            _ => Some(ErrorKind::Other),
+        }
+    }
+
+    pub fn from_error(error: &ureq::Error) -> Self {
+        match error {
+            ureq::Error::StatusCode(status) => Self::from_status(*status).unwrap_or(ErrorKind::Other),
+            ureq::Error::HostNotFound => ErrorKind::Dns,
+            ureq::Error::Tls(_) | ureq::Error::Rustls(_) | ureq::Error::Pem(_) | ureq::Error::TlsRequired => ErrorKind::Tls,
+            ureq::Error::BadUri(_) | ureq::Error::Http(_) | ureq::Error::InvalidProxyUrl => ErrorKind::WrongUrl,
+            ureq::Error::Timeout(_) => ErrorKind::Timeout,
+            ureq::Error::ConnectionFailed | ureq::Error::ConnectProxyFailed(_) | ureq::Error::Io(_) => ErrorKind::Connection,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProviderError {
+    pub kind: ErrorKind,
+    pub status: Option<u16>,
+    pub message: String,
+    pub retry_after: Option<Duration>,
+}
+
+impl ProviderError {
+    pub fn new(kind: ErrorKind, status: Option<u16>, message: String) -> Self {
+        Self { kind, status, message, retry_after: None }
+    }
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.status {
+            Some(status) => write!(f, "{} ({}): {}", self.kind.label(), status, self.message),
+            None => write!(f, "{}: {}", self.kind.label(), self.message),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ConnectionStatus {
+    Testing,
+    Ok { latency: Duration, status: u16 },
+    Failed { latency: Duration, status: Option<u16>, kind: ErrorKind, detail: String },
+}
+
+impl ProviderType {
+    pub fn default_entry_point(&self) -> &'static str {
+        match self {
+            ProviderType::OpenAI => "https://api.openai.com/v1",
+            ProviderType::Ollama => "http://localhost:11434",
+            ProviderType::AzureOpenAI => "",
+            ProviderType::Gemini => "https://generativelanguage.googleapis.com/v1beta",
+            ProviderType::Grog => "https://api.groq.com/openai/v1",
+            ProviderType::Claude => "https://api.anthropic.com/v1",
+            ProviderType::OpenAICompatible => "",
+            ProviderType::Mock => "",
+        }
+    }
+
+    pub fn default_model(&self) -> &'static str {
+        match self {
+            ProviderType::OpenAI => "gpt-4o-mini",
+            ProviderType::Ollama => "llama3.2",
+            ProviderType::AzureOpenAI => "",
+            ProviderType::Gemini => "gemini-1.5-flash",
+            ProviderType::Grog => "llama-3.1-8b-instant",
+            ProviderType::Claude => "claude-3-5-haiku-latest",
+            ProviderType::OpenAICompatible => "",
+            ProviderType::Mock => "mock",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ResolvedSettings {
+    pub api_key: Resolved,
+    pub api_entry_point: Resolved,
+    pub api_deployment: Resolved,
+    pub model: String,
+    pub models: Vec<String>,
+    pub auth: AuthStyle,
+    pub retry: RetryPolicy,
+    pub network: ResolvedNetwork,
+}
+
+impl ResolvedSettings {
+    pub fn entry_point(&self) -> String {
+        let entry_point = self.api_entry_point.value.trim_end_matches('/');
+        if entry_point.is_empty() || entry_point.contains("://") {
+            entry_point.to_string()
+        } else {
+            format!("http://{}", entry_point)
+        }
+    }
+}
+
+impl ProviderSettings {
+    pub fn resolve(&self, provider_type: &ProviderType, vault: Option<&Vault>, env: &Env) -> Result<ResolvedSettings, String> {
+        let mut api_entry_point = resolve_field(self.api_entry_point.as_deref(), env::entry_point_vars(provider_type), vault, env)?;
+        if api_entry_point.value.is_empty() {
+            api_entry_point = Resolved { value: provider_type.default_entry_point().to_string(), source: "default".to_string() };
+        }
+        let api_deployment = resolve_field(self.api_deployment.as_deref(), env::deployment_vars(provider_type), vault, env)?;
+        let model = match self.model.as_deref().filter(|model| !model.is_empty()) {
+            Some(model) => env.interpolate(model)?,
+            None if !api_deployment.value.is_empty() => api_deployment.value.clone(),
+            None if !self.models.is_empty() => self.models[0].clone(),
+            None => provider_type.default_model().to_string(),
+        };
+        Ok(ResolvedSettings {
+            api_key: resolve_field(self.api_key.as_deref(), env::key_vars(provider_type), vault, env)?,
+            api_entry_point,
+            api_deployment,
+            model,
+            models: self.models.clone(),
+            auth: AuthStyle::parse(self.auth_header.as_deref().unwrap_or_default()),
+            retry: self.retry.clone(),
+            network: self.network.resolve(vault, env)?,
+        })
+    }
+}
+
+fn resolve_field(value: Option<&str>, detected_vars: &[&str], vault: Option<&Vault>, env: &Env) -> Result<Resolved, String> {
+    match value.filter(|value| !value.is_empty()) {
+        Some(value) => {
+            let interpolated = env.interpolate(value)?;
+            let mut resolved = secret::resolve(&interpolated, vault, env)?;
+            if interpolated != value && resolved.source == "config" {
+                resolved.source = "config ${...}".to_string();
+            }
+            Ok(resolved)
+        }
+        None => Ok(detected_vars
+            .iter()
+            .find_map(|var| env.get(var).map(|(value, origin)| Resolved { value, source: format!("{} {}", origin, var) }))
+            .unwrap_or_else(|| Resolved { value: String::new(), source: "unset".to_string() })),
+    }
+}
+
+pub fn test_connection(provider_type: &ProviderType, settings: &ResolvedSettings) -> ConnectionStatus {
+    let base = settings.entry_point();
+    let key = settings.api_key.value.as_str();
+
+    let request = match provider_type {
+        ProviderType::OpenAI | ProviderType::Grog if key.is_empty() => {
+            let detail = "no API key configured".to_string();
+            return ConnectionStatus::Failed { latency: Duration::ZERO, status: None, kind: ErrorKind::BadKey, detail };
+        }
+        ProviderType::OpenAI | ProviderType::Grog => AuthStyle::Bearer.apply(HttpRequest::get(format!("{}/models", base)), key),
+        ProviderType::Ollama => HttpRequest::get(format!("{}/api/tags", base)),
+        ProviderType::AzureOpenAI => {
+            HttpRequest::get(format!("{}/openai/models?api-version=2024-02-01", base)).header("api-key", key)
+        }
+        ProviderType::Gemini => HttpRequest::get(format!("{}/models", base)).header("x-goog-api-key", key),
+        ProviderType::Claude => HttpRequest::get(format!("{}/models", base))
+            .header("x-api-key", key)
+            .header("anthropic-version", "2023-06-01"),
+        ProviderType::OpenAICompatible => settings.auth.apply(HttpRequest::get(format!("{}/models", base)), key),
+        ProviderType::Mock => return mock::check(Path::new(&settings.api_entry_point.value)),
+    };
+
+    let started = Instant::now();
+    let timeout = settings.network.timeout.min(Duration::from_secs(10));
+    let result = http::execute(request.timeout(timeout), &settings.network);
+    let latency = started.elapsed();
+
+    match result {
+        Ok(response) => match ErrorKind::from_status(response.status) {
+            None => ConnectionStatus::Ok { latency, status: response.status },
+            Some(kind) => ConnectionStatus::Failed { latency, status: Some(response.status), kind, detail: response.body },
+        },
+        Err(error) => ConnectionStatus::Failed { latency, status: error.status, kind: error.kind, detail: error.message },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{test_connection, AuthStyle, ConnectionStatus, ErrorKind, ProviderSettings, ProviderType};
+    use crate::env::Env;
+    use crate::http::HttpRequest;
+    use crate::snapshot;
+    use std::fs;
+
+    #[test]
+    fn classifies_statuses_and_transport_errors() {
+        assert_eq!(ErrorKind::from_status(200), None);
+        assert_eq!(ErrorKind::from_status(401), Some(ErrorKind::BadKey));
+        assert_eq!(ErrorKind::from_status(404), Some(ErrorKind::WrongUrl));
+        assert_eq!(ErrorKind::from_status(429), Some(ErrorKind::Quota));
+        assert_eq!(ErrorKind::from_status(503), Some(ErrorKind::Server));
+        assert_eq!(ErrorKind::from_status(418), Some(ErrorKind::Other));
+
+        assert_eq!(ErrorKind::from_error(&ureq::Error::StatusCode(403)), ErrorKind::BadKey);
+        assert_eq!(ErrorKind::from_error(&ureq::Error::HostNotFound), ErrorKind::Dns);
+        assert_eq!(ErrorKind::from_error(&ureq::Error::InvalidProxyUrl), ErrorKind::WrongUrl);
+        assert_eq!(ErrorKind::from_error(&ureq::Error::ConnectionFailed), ErrorKind::Connection);
+    }
+
+    #[test]
+    fn resolves_settings_from_config_and_env() {
+        let path = snapshot::temp_dir("provider-resolve").join(".env");
+        fs::write(&path, "GROQ_API_KEY=gsk-from-dotenv\nAI_TEST_HOST=llm.local\n").unwrap();
+        let env = Env::from_file(&path);
+
+        let detected = ProviderSettings::default();
+        let resolved = detected.resolve(&ProviderType::Grog, None, &env).unwrap();
+        assert_eq!((resolved.api_key.value.as_str(), resolved.api_key.source.as_str()), ("gsk-from-dotenv", ".env GROQ_API_KEY"));
+        assert_eq!(resolved.entry_point(), "https://api.groq.com/openai/v1");
+        assert_eq!(resolved.api_entry_point.source, "default");
+
+        let configured = ProviderSettings {
+            api_key: Some("env:GROQ_API_KEY".to_string()),
+            api_entry_point: Some("${AI_TEST_HOST}:8080/".to_string()),
+            ..ProviderSettings::default()
+        };
+        let resolved = configured.resolve(&ProviderType::Grog, None, &env).unwrap();
+        assert_eq!(resolved.api_key.source, ".env GROQ_API_KEY");
+        assert_eq!((resolved.entry_point(), resolved.api_entry_point.source.as_str()), ("http://llm.local:8080".to_string(), "config ${...}"));
+        assert_eq!(resolved.api_deployment.source, "unset");
+    }
+
+    #[test]
+    fn reports_unreachable_providers_as_connection_failures() {
+        let settings = ProviderSettings { api_entry_point: Some("127.0.0.1:1".to_string()), ..ProviderSettings::default() };
+        let settings = settings.resolve(&ProviderType::Ollama, None, &Env::default()).unwrap();
+        assert_eq!(settings.entry_point(), "http://127.0.0.1:1");
+        match test_connection(&ProviderType::Ollama, &settings) {
+            ConnectionStatus::Failed { kind, status, .. } => assert_eq!((kind, status), (ErrorKind::Connection, None)),
+            status => panic!("expected a connection failure, got {:?}", status),
+        }
+
+        let settings = ProviderSettings { api_entry_point: Some("127.0.0.1:1".to_string()), ..ProviderSettings::default() };
+        for provider_type in [ProviderType::OpenAI, ProviderType::Grog] {
+            let mut settings = settings.resolve(&provider_type, None, &Env::default()).unwrap();
+            settings.api_key.value.clear();
+            match test_connection(&provider_type, &settings) {
+                ConnectionStatus::Failed { kind, status, .. } => assert_eq!((kind, status), (ErrorKind::BadKey, None)),
+                status => panic!("expected a missing key, got {:?}", status),
+            }
+        }
+    }
+
+    #[test]
+    fn applies_the_configured_auth_style() {
+        let headers = |auth: &str, key: &str| AuthStyle::parse(auth).apply(HttpRequest::get("http://llm.local/v1/models".to_string()), key).headers;
+        assert_eq!(headers("", "secret"), vec![("Authorization".to_string(), "Bearer secret".to_string())]);
+        assert_eq!(headers("Bearer", ""), Vec::new());
+        assert_eq!(headers(" X-Api-Key ", "secret"), vec![("X-Api-Key".to_string(), "secret".to_string())]);
+        assert_eq!(headers("none", "secret"), Vec::new());
+    }
+
+    #[test]
+    fn resolves_openai_compatible_settings() {
+        let settings = ProviderSettings {
+            api_entry_point: Some("https://llm.local/v1/".to_string()),
+            auth_header: Some("X-Api-Key".to_string()),
+            models: vec!["qwen2.5".to_string(), "mistral".to_string()],
+            ..ProviderSettings::default()
+        };
+        let resolved = settings.resolve(&ProviderType::OpenAICompatible, None, &Env::default()).unwrap();
+        assert_eq!(resolved.entry_point(), "https://llm.local/v1");
+        assert_eq!(resolved.auth, AuthStyle::Header("X-Api-Key".to_string()));
+        assert_eq!(resolved.model, "qwen2.5");
+        assert_eq!(resolved.api_key.source, "unset");
+    }
+}
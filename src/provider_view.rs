@@ -1,139 +1,396 @@
-use crate::traits::View;
-use crossterm::event::KeyCode;
+use crate::keymap::Keymap;
+use crate::mouse;
+use crate::provider::{self, ConnectionStatus, ProviderInstance, ProviderSettings, ProviderType};
+use crate::secret;
+use crate::store::SharedStore;
+use crate::notify::Notifications;
+use crate::theme::Theme;
+use crate::traits::{Action, PaletteEntry, View};
+use crate::widgets::{Checkbox, Dropdown, Field, Form, FormEvent, Modal, ModalOutcome, TextInput};
+use crossterm::event::MouseEvent;
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
-    style::{Color, Modifier, Style},
+    layout::{Constraint, Direction, Layout, Rect},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    widgets::{List, ListItem, ListState, Paragraph},
     Frame,
 };
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
 
-#[derive(Clone, Debug)]
-struct ProviderInstance {
-    name: String,
-    provider_type: ProviderType,
-}
+const TYPE: &str = "Type";
+const API_KEY: &str = "API Key";
+const ENTRY_POINT: &str = "API Entry Point";
+const DEPLOYMENT: &str = "API Deployment";
+const MODEL: &str = "Model";
+const AUTH_HEADER: &str = "Auth Header";
+const MODELS: &str = "Models";
+const TEST_AFTER_SAVE: &str = "Test after save";
 
-#[derive(Clone, Debug)]
-enum ProviderType {
-    OpenAI,
-    Ollama,
-    AzureOpenAI,
-    Gemini,
-    Grog,
-    Claude,
-}
+const LIST_HINTS: [(&str, &str); 4] = [("add", "Add"), ("edit", "Edit"), ("delete", "Delete"), ("test", "Test")];
 
-#[derive(Clone, Debug)]
-struct ProviderSettings {
-    api_key: Option<String>,
-    api_entry_point: Option<String>,
-    api_deployment: Option<String>,
-}
+const PROVIDER_TYPES: [ProviderType; 8] = [
+    ProviderType::OpenAI,
+    ProviderType::Ollama,
+    ProviderType::AzureOpenAI,
+    ProviderType::Gemini,
+    ProviderType::Grog,
+    ProviderType::Claude,
+    ProviderType::OpenAICompatible,
+    ProviderType::Mock,
+];
 
 struct State {
-    provider_instances: HashMap<String, ProviderInstance>,
-    provider_settings: HashMap<String, ProviderSettings>,
-    adding_provider: bool,
+    store: SharedStore,
     editing_provider: Option<String>,
     deleting_provider: Option<String>,
     current_step: Option<AddProviderStep>,
-    settings_input: [String; 3],
-    active_input_index: usize,
+    form: Form,
+    connection_status: Arc<Mutex<HashMap<String, ConnectionStatus>>>,
+    reveal_key: bool,
+    passphrase: TextInput,
+    new_passphrase: Option<String>,
+    after_unlock: AfterUnlock,
 }
 
 enum AddProviderStep {
-    SelectType,
     ConfigureSettings,
+    UnlockVault,
+}
+
+enum AfterUnlock {
+    Save,
+    Delete(String),
 }
 
 pub struct ProviderView {
     state: State,
     provider_list_state: ListState,
-    provider_type_list_state: ListState,
-    selected_provider_type: Option<ProviderType>,
+    modal: Option<Modal>,
+    list_area: Cell<Rect>,
+    list_offset: Cell<usize>,
+    actions: Vec<String>,
+    reveal_hint: String,
 }
 
 impl ProviderView {
-    pub fn new() -> Self {
+    pub fn new(store: SharedStore) -> Self {
         let mut provider_list_state = ListState::default();
         provider_list_state.select(Some(0));
 
-        let mut provider_type_list_state = ListState::default();
-        provider_type_list_state.select(Some(0));
+        let mut passphrase = TextInput::new();
+        passphrase.set_mask(Some(|value| "•".repeat(value.chars().count())));
 
         let state = State {
-            provider_instances: HashMap::new(),
-            provider_settings: HashMap::new(),
-            adding_provider: false,
+            store,
             editing_provider: None,
             deleting_provider: None,
             current_step: None,
-            settings_input: Default::default(),
-            active_input_index: 0,
+            form: Self::settings_form(None, ProviderSettings::default()),
+            connection_status: Arc::new(Mutex::new(HashMap::new())),
+            reveal_key: false,
+            passphrase,
+            new_passphrase: None,
+            after_unlock: AfterUnlock::Save,
         };
 
-        Self {
+        let mut view = Self {
             state,
             provider_list_state,
-            provider_type_list_state,
-            selected_provider_type: None,
+            modal: None,
+            list_area: Cell::default(),
+            list_offset: Cell::default(),
+            actions: Vec::new(),
+            reveal_hint: String::new(),
+        };
+        view.set_keymap(&Keymap::default());
+        view
+    }
+
+    fn settings_form(provider_type: Option<&ProviderType>, settings: ProviderSettings) -> Form {
+        let mut types = Dropdown::new(PROVIDER_TYPES.iter().map(|provider_type| format!("{:?}", provider_type)).collect());
+        match provider_type {
+            Some(provider_type) => types.select(PROVIDER_TYPES.iter().position(|candidate| candidate == provider_type).unwrap_or(0)),
+            None => types.open(),
         }
+        let text = |value: String| {
+            let mut input = TextInput::new();
+            input.set_value(value);
+            Field::Text(input)
+        };
+        let mut api_key = TextInput::new();
+        api_key.set_value(settings.api_key.unwrap_or_default());
+        api_key.set_mask(Some(secret::mask));
+        Form::new(vec![
+            (TYPE, Field::Dropdown(types)),
+            (API_KEY, Field::Text(api_key)),
+            (ENTRY_POINT, text(settings.api_entry_point.unwrap_or_default())),
+            (DEPLOYMENT, text(settings.api_deployment.unwrap_or_default())),
+            (MODEL, text(settings.model.unwrap_or_default())),
+            (AUTH_HEADER, text(settings.auth_header.unwrap_or_default())),
+            (MODELS, text(settings.models.join(", "))),
+            (TEST_AFTER_SAVE, Field::Checkbox(Checkbox::default())),
+        ])
     }
 
-    fn get_provider_type_names() -> Vec<&'static str> {
-        vec![
-            "OpenAI",
-            "Ollama",
-            "AzureOpenAI",
-            "Gemini",
-            "Grog",
-            "Claude",
-        ]
+    fn set_reveal_key(&mut self, reveal: bool) {
+        self.state.reveal_key = reveal;
+        if let Some(Field::Text(input)) = self.state.form.get_mut(API_KEY) {
+            input.set_mask(if reveal { None } else { Some(secret::mask) });
+        }
     }
 
-    fn render_provider_settings(&self) -> List {
-        let settings = vec![
-            ListItem::new(Span::styled(
-                format!("API Key: {}", self.state.settings_input[0]),
-                if self.state.active_input_index == 0 {
-                    Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
-                } else {
-                    Style::default()
-                },
-            )),
-            ListItem::new(Span::styled(
-                format!("API Entry Point: {}", self.state.settings_input[1]),
-                if self.state.active_input_index == 1 {
-                    Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
-                } else {
-                    Style::default()
-                },
-            )),
-            ListItem::new(Span::styled(
-                format!("API Deployment: {}", self.state.settings_input[2]),
-                if self.state.active_input_index == 2 {
-                    Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
-                } else {
-                    Style::default()
-                },
-            )),
-        ];
+    fn close_form(&mut self) {
+        self.state.current_step = None;
+        self.state.editing_provider = None;
+        self.set_reveal_key(false);
+    }
+
+    fn status_badge(&self, name: &str, theme: &Theme) -> Span<'static> {
+        let connection_status = self.state.connection_status.lock().unwrap();
+        match connection_status.get(name) {
+            None => Span::styled(" [untested]", theme.muted),
+            Some(ConnectionStatus::Testing) => Span::styled(" [testing...]", theme.warning),
+            Some(ConnectionStatus::Ok { latency, status }) => Span::styled(
+                format!(" [OK {} {}ms]", status, latency.as_millis()),
+                theme.success,
+            ),
+            Some(ConnectionStatus::Failed { latency, status, kind, detail }) => Span::styled(
+                format!(
+                    " [{}{} {}ms] {}",
+                    kind.label(),
+                    status.map(|status| format!(" {}", status)).unwrap_or_default(),
+                    latency.as_millis(),
+                    detail.lines().next().unwrap_or_default(),
+                ),
+                theme.error,
+            ),
+        }
+    }
+
+    fn settings_summary(&self, name: &str) -> String {
+        let store = self.state.store.borrow();
+        let Some(instance) = store.instances.get(name) else {
+            return String::new();
+        };
+        match store.resolve(name) {
+            Ok((_, resolved)) if matches!(instance.provider_type, ProviderType::Mock) => format!(
+                " ({:?} {}) fixture: {} ({})",
+                instance.provider_type, resolved.model, resolved.api_entry_point.value, resolved.api_entry_point.source,
+            ),
+            Ok((_, resolved)) => {
+                let mut summary = format!(
+                    " ({:?} {}) key: {} url: {} ({})",
+                    instance.provider_type,
+                    resolved.model,
+                    resolved.api_key.describe(),
+                    resolved.entry_point(),
+                    resolved.api_entry_point.source,
+                );
+                if let Some(proxy) = &resolved.network.proxy {
+                    let host = proxy.rsplit('@').next().unwrap_or_default();
+                    summary.push_str(&format!(" proxy: {}", host));
+                }
+                if !resolved.api_deployment.value.is_empty() {
+                    summary.push_str(&format!(" deployment: {} ({})", resolved.api_deployment.value, resolved.api_deployment.source));
+                }
+                summary
+            }
+            Err(e) => format!(" ({:?}) {}", instance.provider_type, e),
+        }
+    }
+
+    fn test_provider(&self, name: &str) -> Result<(), String> {
+        let (instance, settings) = self.state.store.borrow().resolve(name)?;
+        let connection_status = Arc::clone(&self.state.connection_status);
+        connection_status.lock().unwrap().insert(instance.name.clone(), ConnectionStatus::Testing);
+        thread::spawn(move || {
+            let status = provider::test_connection(&instance.provider_type, &settings);
+            connection_status.lock().unwrap().insert(instance.name, status);
+        });
+        Ok(())
+    }
+
+    fn request_unlock(&mut self, after_unlock: AfterUnlock, reason: &str, notifications: &mut Notifications) {
+        self.state.current_step = Some(AddProviderStep::UnlockVault);
+        self.state.after_unlock = after_unlock;
+        if self.state.store.borrow().vault_exists() {
+            notifications.info(format!("Enter the vault passphrase {}", reason));
+        } else {
+            notifications.info(format!("Choose a passphrase for the new vault {}", reason));
+        }
+    }
+
+    fn submit_passphrase(&mut self, notifications: &mut Notifications) {
+        let passphrase = self.state.passphrase.take();
+        let mut store = self.state.store.borrow_mut();
+        let unlocked = if store.vault_exists() {
+            store.unlock_vault(&passphrase)
+        } else {
+            match self.state.new_passphrase.take() {
+                _ if passphrase.is_empty() => {
+                    notifications.error("The vault passphrase must not be empty");
+                    return;
+                }
+                None => {
+                    self.state.new_passphrase = Some(passphrase);
+                    notifications.info("Enter the passphrase again to confirm it");
+                    return;
+                }
+                Some(first) if first != passphrase => {
+                    notifications.error("Passphrases do not match, choose the passphrase again");
+                    return;
+                }
+                Some(_) => store.create_vault(&passphrase),
+            }
+        };
+        drop(store);
+        match unlocked {
+            Ok(()) => match std::mem::replace(&mut self.state.after_unlock, AfterUnlock::Save) {
+                AfterUnlock::Save => self.save_provider(notifications),
+                AfterUnlock::Delete(name) => {
+                    self.state.current_step = None;
+                    self.delete_provider(&name, notifications);
+                }
+            },
+            Err(e) => notifications.error(format!("Failed to unlock vault: {}", e)),
+        }
+    }
+
+    fn cancel_unlock(&mut self) {
+        self.state.passphrase.take();
+        self.state.new_passphrase = None;
+        self.state.current_step = match std::mem::replace(&mut self.state.after_unlock, AfterUnlock::Save) {
+            AfterUnlock::Save => Some(AddProviderStep::ConfigureSettings),
+            AfterUnlock::Delete(_) => None,
+        };
+    }
+
+    fn save_provider(&mut self, notifications: &mut Notifications) {
+        let mut store = self.state.store.borrow_mut();
+        let form = &self.state.form;
+        let provider_name = self.state.editing_provider.clone().unwrap_or_else(|| store.new_name());
+        let provider_type = PROVIDER_TYPES[form.selected(TYPE)].clone();
+
+        let mut api_key = form.text(API_KEY).to_string();
+        let plaintext = !api_key.is_empty() && !secret::is_reference(&api_key);
+        if store.vault.is_none() && (plaintext || !store.plaintext_keys().is_empty()) {
+            drop(store);
+            let reason = if plaintext { "to store the API key" } else { "to move plaintext API keys into it" };
+            self.request_unlock(AfterUnlock::Save, reason, notifications);
+            return;
+        }
+        if plaintext {
+            let vault = store.vault.as_mut().unwrap();
+            if let Err(e) = vault.set(&provider_name, &api_key) {
+                notifications.error(format!("Failed to store API key: {}", e));
+                return;
+            }
+            api_key = format!("vault:{}", provider_name);
+        }
+
+        let provider_instance = ProviderInstance {
+            name: provider_name.clone(),
+            provider_type,
+        };
+        let provider_settings = ProviderSettings {
+            api_key: Some(api_key),
+            api_entry_point: Some(form.text(ENTRY_POINT).to_string()),
+            api_deployment: Some(form.text(DEPLOYMENT).to_string()),
+            model: Some(form.text(MODEL).to_string()),
+            auth_header: Some(form.text(AUTH_HEADER).to_string()),
+            models: form
+                .text(MODELS)
+                .split(',')
+                .map(str::trim)
+                .filter(|model| !model.is_empty())
+                .map(str::to_string)
+                .collect(),
+            ..store.settings.get(&provider_name).cloned().unwrap_or_default()
+        };
+        let test_after_save = form.checked(TEST_AFTER_SAVE);
+
+        store.update(provider_instance, provider_settings);
+        let saved = store.save();
+        drop(store);
+        self.close_form();
+        match saved {
+            Ok(()) => notifications.success("Provider saved successfully."),
+            Err(e) => {
+                self.modal = Some(Modal::alert("Save failed", format!("Failed to save providers: {}", e)));
+                return;
+            }
+        }
+        if test_after_save {
+            if let Err(e) = self.test_provider(&provider_name) {
+                notifications.error(format!("Cannot test {}: {}", provider_name, e));
+            }
+        }
+    }
+
+    fn delete_provider(&mut self, name: &str, notifications: &mut Notifications) {
+        let mut store = self.state.store.borrow_mut();
+        if store.vault.is_none() && store.vault_secret(name).is_some() {
+            drop(store);
+            self.request_unlock(AfterUnlock::Delete(name.to_string()), "to delete the API key", notifications);
+            return;
+        }
+        let removed = store.remove(name).and_then(|()| store.save());
+        let count = store.names().len();
+        match removed {
+            Ok(()) => notifications.success(format!("Deleted provider {}", name)),
+            Err(e) => self.modal = Some(Modal::alert("Delete failed", format!("Failed to save providers: {}", e))),
+        }
+        let selected = self.provider_list_state.selected().unwrap_or_default();
+        self.provider_list_state.select(Some(selected.min(count.saturating_sub(1))));
+    }
+
+    fn close_modal(&mut self, outcome: ModalOutcome, notifications: &mut Notifications) {
+        if matches!(outcome, ModalOutcome::Open) {
+            return;
+        }
+        self.modal = None;
+        if let Some(name) = self.state.deleting_provider.take() {
+            if matches!(outcome, ModalOutcome::Confirmed) {
+                self.delete_provider(&name, notifications);
+            }
+        }
+    }
 
-        let settings_list = List::new(settings)
-            .block(Block::default().borders(Borders::ALL).title("Provider Settings"))
-            .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
-            .highlight_symbol(">> ");
-        settings_list
+    fn selected_name(&self) -> Option<String> {
+        let selected = self.provider_list_state.selected()?;
+        self.state.store.borrow().names().get(selected).cloned()
     }
 }
 
 impl View for ProviderView {
-    fn render(&self, f: &mut Frame, area: ratatui::layout::Rect, info_message: &str) {
+    fn title(&self) -> &'static str {
+        "Provider View"
+    }
+
+    fn shortcut(&self) -> char {
+        '2'
+    }
+
+    fn hints(&self) -> &'static [(&'static str, &'static str)] {
+        if self.modal.is_some() {
+            &[("accept", "Yes"), ("reject", "No"), ("confirm", "Choose"), ("cancel", "Cancel")]
+        } else if self.state.current_step.is_some() {
+            &[("next_field", "Next field"), ("reveal_key", "Reveal key"), ("confirm", "Save"), ("cancel", "Cancel")]
+        } else {
+            &LIST_HINTS
+        }
+    }
+
+    fn set_keymap(&mut self, keymap: &Keymap) {
+        self.actions = LIST_HINTS.iter().map(|hint| keymap.hints("provider", &[*hint])).filter(|hint| !hint.is_empty()).collect();
+        self.reveal_hint = keymap.hints("provider_form", &[("reveal_key", "reveal key")]);
+    }
+
+    fn render(&self, f: &mut Frame, area: ratatui::layout::Rect, theme: &Theme) {
         let constraints = vec![
             Constraint::Length(1),
-            Constraint::Length(3),
             Constraint::Min(0),
         ];
 
@@ -142,185 +399,334 @@ impl View for ProviderView {
             .constraints(constraints.as_slice())
             .split(area);
 
-        let actions_line = Line::from(vec![
-            Span::styled(" [a] Add ", Style::default().fg(Color::Green)),
-            Span::styled(" [e] Edit ", Style::default().fg(Color::Green)),
-            Span::styled(" [d] Delete ", Style::default().fg(Color::Green)),
-        ]);
+        let actions_line =
+            Line::from(self.actions.iter().map(|hint| Span::styled(format!(" {} ", hint), theme.success)).collect::<Vec<_>>());
         let actions = Paragraph::new(actions_line);
         f.render_widget(actions, chunks[0]);
 
-        let info_paragraph = Paragraph::new(info_message)
-            .block(Block::default().borders(Borders::ALL).title("Info / Command"));
-        f.render_widget(info_paragraph, chunks[chunks.len() - 2]);
-
         match self.state.current_step {
-            Some(AddProviderStep::SelectType) => {
-                let provider_types = Self::get_provider_type_names();
-                let items: Vec<ListItem> = provider_types.iter().map(|&name| ListItem::new(Span::raw(name))).collect();
-                let list = List::new(items)
-                    .block(Block::default().borders(Borders::ALL).title("Select Provider Type"))
-                    .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
-                    .highlight_symbol(">> ");
-                f.render_stateful_widget(list, chunks[chunks.len() - 1], &mut self.provider_type_list_state.clone());
-            }
             Some(AddProviderStep::ConfigureSettings) => {
-                let settings_list = self.render_provider_settings();
-                f.render_stateful_widget(settings_list, chunks[chunks.len() - 1], &mut ListState::default().with_selected(Option::from(self.state.active_input_index)));
+                self.state.form.render(f, chunks[1], "Provider Settings", theme);
+            }
+            Some(AddProviderStep::UnlockVault) => {
+                let title = if self.state.new_passphrase.is_some() {
+                    "Confirm Vault Passphrase"
+                } else if self.state.store.borrow().vault_exists() {
+                    "Unlock Vault"
+                } else {
+                    "Create Vault"
+                };
+                let block = theme.block(title);
+                let inner = block.inner(chunks[1]);
+                f.render_widget(block, chunks[1]);
+                let label = "Passphrase: ";
+                f.render_widget(Paragraph::new(label).style(theme.text), inner);
+                let input = Rect { x: inner.x + label.len() as u16, width: inner.width.saturating_sub(label.len() as u16), height: 1.min(inner.height), ..inner };
+                self.state.passphrase.render(f, input, theme.text, true);
             }
             None => {
-                let providers: Vec<String> = self.state.provider_instances.keys().cloned().collect();
-                let items: Vec<ListItem> = providers.iter().map(|name| ListItem::new(Span::raw(name))).collect();
+                let providers = self.state.store.borrow().names();
+                let items: Vec<ListItem> = providers
+                    .iter()
+                    .map(|name| {
+                        ListItem::new(Line::from(vec![
+                            Span::raw(name.clone()),
+                            Span::styled(self.settings_summary(name), theme.muted),
+                            self.status_badge(name, theme),
+                        ]))
+                    })
+                    .collect();
                 let list = List::new(items)
-                    .block(Block::default().borders(Borders::ALL).title("Providers"))
-                    .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+                    .block(theme.block("Providers"))
+                    .highlight_style(theme.highlight)
                     .highlight_symbol(">> ");
-                f.render_stateful_widget(list, chunks[chunks.len() - 1], &mut self.provider_list_state.clone());
+                let mut list_state = self.provider_list_state.clone();
+                f.render_stateful_widget(list, chunks[1], &mut list_state);
+                self.list_offset.set(list_state.offset());
             }
         }
+        self.list_area.set(chunks[1]);
+        if let Some(modal) = &self.modal {
+            modal.render(f, area, theme);
+        }
     }
 
-    fn handle_input(&mut self, key: crossterm::event::KeyEvent, info_message: &mut String) {
+    fn palette_entries(&self) -> Vec<PaletteEntry> {
+        self.state
+            .store
+            .borrow()
+            .names()
+            .into_iter()
+            .map(|name| PaletteEntry { label: format!("Provider {}", name), command: format!("select:{}", name) })
+            .collect()
+    }
+
+    fn run_command(&mut self, command: &str, _notifications: &mut Notifications) -> Action {
+        if let Some(name) = command.strip_prefix("select:") {
+            if let Some(index) = self.state.store.borrow().names().iter().position(|candidate| candidate == name) {
+                self.provider_list_state.select(Some(index));
+            }
+        }
+        Action::None
+    }
+
+    fn handle_mouse(&mut self, event: MouseEvent, notifications: &mut Notifications) -> Action {
+        if let Some(modal) = &mut self.modal {
+            let outcome = modal.handle_mouse(&event);
+            self.close_modal(outcome, notifications);
+            return Action::None;
+        }
         match self.state.current_step {
-            Some(AddProviderStep::SelectType) => {
-                match key.code {
-                    KeyCode::Down => {
-                        let provider_types = Self::get_provider_type_names();
-                        if let Some(selected) = self.provider_type_list_state.selected() {
-                            if selected < provider_types.len() - 1 {
-                                self.provider_type_list_state.select(Some(selected + 1));
-                            }
-                        }
-                    }
-                    KeyCode::Up => {
-                        if let Some(selected) = self.provider_type_list_state.selected() {
-                            if selected > 0 {
-                                self.provider_type_list_state.select(Some(selected - 1));
-                            }
-                        }
-                    }
-                    KeyCode::Enter => {
-                        if let Some(selected) = self.provider_type_list_state.selected() {
-                            let provider_types = Self::get_provider_type_names();
-                            self.selected_provider_type = match provider_types[selected] {
-                                "OpenAI" => Some(ProviderType::OpenAI),
-                                "Ollama" => Some(ProviderType::Ollama),
-                                "AzureOpenAI" => Some(ProviderType::AzureOpenAI),
-                                "Gemini" => Some(ProviderType::Gemini),
-                                "Grog" => Some(ProviderType::Grog),
-                                "Claude" => Some(ProviderType::Claude),
-                                _ => None,
-                            };
-                            self.state.current_step = Some(AddProviderStep::ConfigureSettings);
-                            info_message.clear();
-                            info_message.push_str("Configure the selected provider settings");
-                        }
-                    }
-                    KeyCode::Esc => {
-                        self.state.current_step = None;
-                        info_message.clear();
-                    }
-                    _ => {}
+            Some(AddProviderStep::ConfigureSettings) => self.state.form.handle_mouse(&event),
+            Some(AddProviderStep::UnlockVault) => {}
+            None => {
+                let len = self.state.store.borrow().names().len();
+                if let Some(index) = mouse::list_row(self.list_area.get(), self.list_offset.get(), len, &event) {
+                    self.provider_list_state.select(Some(index));
                 }
             }
+        }
+        Action::None
+    }
+
+    fn handle_paste(&mut self, text: &str) {
+        match self.state.current_step {
+            Some(AddProviderStep::ConfigureSettings) if self.modal.is_none() => self.state.form.handle_paste(text),
+            Some(AddProviderStep::UnlockVault) if self.modal.is_none() => self.state.passphrase.insert_str(text),
+            _ => {}
+        }
+    }
+
+    fn wants_raw_input(&self) -> bool {
+        self.modal.is_some() || self.state.current_step.is_some()
+    }
+
+    fn keymap_scope(&self) -> &'static str {
+        if self.modal.is_some() {
+            "modal"
+        } else if self.state.current_step.is_some() {
+            "provider_form"
+        } else {
+            "provider"
+        }
+    }
+
+    fn handle_input(&mut self, key: crossterm::event::KeyEvent, binding: Option<&str>, notifications: &mut Notifications) -> Action {
+        if let Some(modal) = &mut self.modal {
+            let outcome = modal.handle_input(key, binding);
+            self.close_modal(outcome, notifications);
+            return Action::None;
+        }
+        match self.state.current_step {
             Some(AddProviderStep::ConfigureSettings) => {
-                match key.code {
-                    KeyCode::Tab => {
-                        self.state.active_input_index = (self.state.active_input_index + 1) % 3;
-                    }
-                    KeyCode::BackTab => {
-                        if self.state.active_input_index == 0 {
-                            self.state.active_input_index = 2;
-                        } else {
-                            self.state.active_input_index -= 1;
-                        }
-                    }
-                    KeyCode::Char(c) => {
-                        self.state.settings_input[self.state.active_input_index].push(c);
-                    }
-                    KeyCode::Enter => {
-                        let provider_name = format!("Provider{}", self.state.provider_instances.len() + 1);
-                        let provider_instance = ProviderInstance {
-                            name: provider_name.clone(),
-                            provider_type: self.selected_provider_type.clone().unwrap(),
-                        };
-                        let provider_settings = ProviderSettings {
-                            api_key: Some(self.state.settings_input[0].clone()),
-                            api_entry_point: Some(self.state.settings_input[1].clone()),
-                            api_deployment: Some(self.state.settings_input[2].clone()),
-                        };
-
-                        self.state.provider_instances.insert(provider_name.clone(), provider_instance);
-                        self.state.provider_settings.insert(provider_name.clone(), provider_settings);
-
-                        self.state.current_step = None;
-                        info_message.clear();
-                        info_message.push_str("Provider added successfully.");
-                        self.state.settings_input = Default::default();
-                    }
-                    KeyCode::Backspace => {
-                        if !self.state.settings_input[self.state.active_input_index].is_empty() {
-                            self.state.settings_input[self.state.active_input_index].pop();
+                if binding == Some("reveal_key") {
+                    self.set_reveal_key(!self.state.reveal_key);
+                    return Action::None;
+                }
+                let provider_type = self.state.form.selected(TYPE);
+                match self.state.form.handle_input(key, binding) {
+                    FormEvent::Submit => self.save_provider(notifications),
+                    FormEvent::Cancel => self.close_form(),
+                    FormEvent::None => {
+                        let selected = self.state.form.selected(TYPE);
+                        if selected != provider_type && PROVIDER_TYPES[selected] == ProviderType::Mock {
+                            notifications.info("Set API Entry Point to the YAML/JSON fixture file with the scripted responses");
                         }
                     }
-                    KeyCode::Esc => {
-                        self.state.current_step = None;
-                        info_message.clear();
+                }
+            }
+            Some(AddProviderStep::UnlockVault) => {
+                match binding {
+                    Some("confirm") => self.submit_passphrase(notifications),
+                    Some("cancel") => self.cancel_unlock(),
+                    _ => {
+                        self.state.passphrase.handle_input(key, binding);
                     }
-                    _ => {}
                 }
             }
             None => {
-                match key.code {
-                    KeyCode::Char('a') => {
-                        self.state.adding_provider = true;
-                        self.state.current_step = Some(AddProviderStep::SelectType);
-                        info_message.clear();
-                        info_message.push_str("Select the provider type");
+                match binding {
+                    Some("add") => {
+                        self.state.form = Self::settings_form(None, ProviderSettings::default());
+                        self.state.current_step = Some(AddProviderStep::ConfigureSettings);
+                        let reveal = if self.reveal_hint.is_empty() { String::new() } else { format!("{}, ", self.reveal_hint) };
+                        notifications.info(format!(
+                            "Select the provider type, then configure its settings ({}use env:VAR or cmd:... to reference a key)",
+                            reveal
+                        ));
                     }
-                    KeyCode::Char('e') => {
-                        if let Some(selected) = self.provider_list_state.selected() {
-                            let provider_names = self.state.provider_instances.keys().cloned().collect::<Vec<_>>();
-                            if selected < provider_names.len() {
-                                self.state.editing_provider = Some(provider_names[selected].clone());
-                                let settings = self.state.provider_settings.get(&provider_names[selected]).unwrap();
-                                self.state.settings_input[0] = settings.api_key.clone().unwrap_or_default();
-                                self.state.settings_input[1] = settings.api_entry_point.clone().unwrap_or_default();
-                                self.state.settings_input[2] = settings.api_deployment.clone().unwrap_or_default();
-                                self.state.current_step = Some(AddProviderStep::ConfigureSettings);
-                                info_message.clear();
-                                info_message.push_str(&format!("Edit Provider action triggered for {}", provider_names[selected]));
-                            }
+                    Some("edit") => {
+                        if let Some(name) = self.selected_name() {
+                            let store = self.state.store.borrow();
+                            let provider_type = store.instances.get(&name).map(|instance| instance.provider_type.clone());
+                            let settings = store.settings.get(&name).cloned().unwrap_or_default();
+                            drop(store);
+                            self.state.form = Self::settings_form(provider_type.as_ref(), settings);
+                            self.state.form.set_focus(1);
+                            self.state.editing_provider = Some(name.clone());
+                            self.state.current_step = Some(AddProviderStep::ConfigureSettings);
+                            notifications.info(format!("Editing provider {}", name));
                         }
                     }
-                    KeyCode::Char('d') => {
-                        if let Some(selected) = self.provider_list_state.selected() {
-                            let provider_names = self.state.provider_instances.keys().cloned().collect::<Vec<_>>();
-                            if selected < provider_names.len() {
-                                self.state.deleting_provider = Some(provider_names[selected].clone());
-                                info_message.clear();
-                                info_message.push_str(&format!("Delete Provider action triggered for {}", provider_names[selected]));
+                    Some("delete") => {
+                        if let Some(name) = self.selected_name() {
+                            self.modal = Some(Modal::confirm("Delete Provider", format!("Delete provider {}?", name)));
+                            self.state.deleting_provider = Some(name);
+                        }
+                    }
+                    Some("test") => {
+                        if let Some(name) = self.selected_name() {
+                            match self.test_provider(&name) {
+                                Ok(()) => notifications.info(format!("Testing connection for {}", name)),
+                                Err(e) => notifications.error(format!("Cannot test {}: {}", name, e)),
                             }
                         }
                     }
-                    KeyCode::Down => {
+                    Some("select_next") => {
                         if let Some(selected) = self.provider_list_state.selected() {
-                            let provider_names = self.state.provider_instances.keys().cloned().collect::<Vec<_>>();
-                            if selected < provider_names.len() - 1 {
+                            let provider_names = self.state.store.borrow().names();
+                            if selected + 1 < provider_names.len() {
                                 self.provider_list_state.select(Some(selected + 1));
                             }
                         }
                     }
-                    KeyCode::Up => {
+                    Some("select_prev") => {
                         if let Some(selected) = self.provider_list_state.selected() {
                             if selected > 0 {
                                 self.provider_list_state.select(Some(selected - 1));
                             }
                         }
                     }
+                    Some("select_first") => {
+                        self.provider_list_state.select(Some(0));
+                    }
+                    Some("select_last") => {
+                        let count = self.state.store.borrow().names().len();
+                        self.provider_list_state.select(Some(count.saturating_sub(1)));
+                    }
                     _ => {}
                 }
             }
         }
+        Action::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProviderView;
+    use crate::snapshot::{self, click, ctrl, key, Harness};
+    use crossterm::event::KeyCode;
+
+    #[test]
+    fn renders_empty_provider_list() {
+        let (store, _) = snapshot::store("provider-empty");
+        Harness::new(ProviderView::new(store), 100, 16).assert_snapshot("provider_view_empty");
+    }
+
+    #[test]
+    fn adds_mock_provider() {
+        let (store, _) = snapshot::store("provider-add");
+        let mut harness = Harness::new(ProviderView::new(store.clone()), 100, 16);
+        harness.keys(&[key(KeyCode::Char('a'))]).assert_snapshot("provider_view_select_type");
+        harness
+            .keys(&[key(KeyCode::Down); 7])
+            .keys(&[key(KeyCode::Enter)])
+            .assert_snapshot("provider_view_configure_mock");
+        harness
+            .keys(&[key(KeyCode::Tab); 2])
+            .type_text("fixture.yaml")
+            .keys(&[key(KeyCode::Enter)])
+            .assert_snapshot("provider_view_mock_added");
+        assert_eq!(store.borrow().names(), vec!["Provider1".to_string()]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn picks_type_and_field_with_mouse() {
+        let (store, _) = snapshot::store("provider-mouse");
+        let mut harness = Harness::new(ProviderView::new(store.clone()), 100, 16);
+        harness
+            .keys(&[key(KeyCode::Char('a'))])
+            .mouse(&[click(12, 11)])
+            .assert_snapshot("provider_view_configure_mock");
+        harness
+            .mouse(&[click(5, 4)])
+            .type_text("fixture.yaml")
+            .keys(&[key(KeyCode::Enter)])
+            .assert_snapshot("provider_view_mock_added");
+        assert_eq!(store.borrow().names(), vec!["Provider1".to_string()]);
+    }
+
+    #[test]
+    fn masks_key_until_revealed() {
+        let (store, _) = snapshot::store("provider-mask");
+        let mut harness = Harness::new(ProviderView::new(store), 100, 16);
+        harness
+            .keys(&[key(KeyCode::Char('a')), key(KeyCode::Enter), key(KeyCode::Tab)])
+            .type_text("sk-test-0123456789")
+            .assert_snapshot("provider_view_key_masked")
+            .keys(&[ctrl('r')])
+            .assert_snapshot("provider_view_key_revealed");
+    }
+
+    #[test]
+    fn confirms_before_deleting() {
+        let (store, _) = snapshot::store("provider-delete");
+        let mut harness = Harness::new(ProviderView::new(store.clone()), 100, 16);
+        harness
+            .keys(&[key(KeyCode::Char('a')), key(KeyCode::Enter)])
+            .keys(&[key(KeyCode::Enter)])
+            .keys(&[key(KeyCode::Char('d'))])
+            .assert_snapshot("provider_view_confirm_delete")
+            .keys(&[key(KeyCode::Char('n'))]);
+        assert_eq!(store.borrow().names(), vec!["Provider1".to_string()]);
+
+        harness.keys(&[key(KeyCode::Char('d')), key(KeyCode::Tab), key(KeyCode::Enter)]);
+        assert!(store.borrow().names().is_empty());
+        assert_eq!(harness.notifications.latest().unwrap().message, "Deleted provider Provider1");
+    }
+
+    #[test]
+    fn names_new_providers_after_deletions() {
+        let (store, _) = snapshot::store("provider-names");
+        let mut harness = Harness::new(ProviderView::new(store.clone()), 100, 16);
+        let add = [key(KeyCode::Char('a')), key(KeyCode::Enter), key(KeyCode::Enter)];
+        harness.keys(&add).keys(&add);
+        harness.keys(&[key(KeyCode::Up), key(KeyCode::Char('d')), key(KeyCode::Tab), key(KeyCode::Enter)]);
+        assert_eq!(store.borrow().names(), vec!["Provider2".to_string()]);
+        harness.keys(&add);
+        assert_eq!(store.borrow().names(), vec!["Provider2".to_string(), "Provider3".to_string()]);
+    }
+
+    #[test]
+    fn creates_vault_after_confirming_passphrase_and_deletes_secret() {
+        let (store, _) = snapshot::store("provider-vault");
+        let mut harness = Harness::new(ProviderView::new(store.clone()), 100, 16);
+        harness
+            .keys(&[key(KeyCode::Char('a')), key(KeyCode::Enter), key(KeyCode::Tab)])
+            .type_text("sk-test-0123456789")
+            .keys(&[key(KeyCode::Enter)])
+            .type_text("first")
+            .keys(&[key(KeyCode::Enter)])
+            .type_text("second")
+            .keys(&[key(KeyCode::Enter)]);
+        assert_eq!(harness.notifications.latest().unwrap().message, "Passphrases do not match, choose the passphrase again");
+        assert!(!store.borrow().vault_exists());
+
+        harness
+            .type_text("secret")
+            .keys(&[key(KeyCode::Enter)])
+            .type_text("secret")
+            .keys(&[key(KeyCode::Enter)]);
+        assert_eq!(store.borrow().settings["Provider1"].api_key.as_deref(), Some("vault:Provider1"));
+        assert_eq!(store.borrow().vault.as_ref().unwrap().get("Provider1"), Some("sk-test-0123456789"));
+
+        store.borrow_mut().vault = None;
+        harness
+            .keys(&[key(KeyCode::Char('d')), key(KeyCode::Tab), key(KeyCode::Enter)])
+            .type_text("secret")
+            .keys(&[key(KeyCode::Enter)]);
+        assert!(store.borrow().names().is_empty());
+        assert_eq!(store.borrow().vault.as_ref().unwrap().get("Provider1"), None);
+    }
+}
@@ -0,0 +1,48 @@
+use crate::{
+    chat_view::ChatView, context_view::ContextView, log_view::LogView, provider_view::ProviderView, store::SharedStore, traits::View,
+    usage::SharedLedger, usage_view::UsageView,
+};
+
+pub struct ViewRegistry {
+    views: Vec<Box<dyn View>>,
+}
+
+impl ViewRegistry {
+    pub fn new() -> Self {
+        Self { views: Vec::new() }
+    }
+
+    pub fn with_defaults(store: SharedStore, ledger: SharedLedger) -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(ContextView::new()));
+        registry.register(Box::new(ProviderView::new(store.clone())));
+        registry.register(Box::new(ChatView::new(store, ledger.clone())));
+        registry.register(Box::new(UsageView::new(ledger)));
+        registry.register(Box::new(LogView::new()));
+        registry
+    }
+
+    pub fn register(&mut self, view: Box<dyn View>) {
+        self.views.push(view);
+    }
+
+    pub fn len(&self) -> usize {
+        self.views.len()
+    }
+
+    pub fn get(&self, index: usize) -> &dyn View {
+        self.views[index].as_ref()
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> &mut dyn View {
+        self.views[index].as_mut()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn View> {
+        self.views.iter().map(|view| view.as_ref())
+    }
+
+    pub fn by_shortcut(&self, shortcut: char) -> Option<usize> {
+        self.views.iter().position(|view| view.shortcut() == shortcut)
+    }
+}
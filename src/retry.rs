@@ -0,0 +1,203 @@
+use crate::provider::{ErrorKind, ProviderError};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_retries: 3, initial_backoff_ms: 500, max_backoff_ms: 30_000 }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns `None` when the server asks us to wait longer than `max_backoff_ms`, in which
+    /// case the error is returned so a route can fall back instead of stalling.
+    pub fn backoff(&self, attempt: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        if let Some(retry_after) = retry_after {
+            return (retry_after <= Duration::from_millis(self.max_backoff_ms)).then_some(retry_after);
+        }
+        let base = self
+            .initial_backoff_ms
+            .saturating_mul(1u64 << attempt.min(20))
+            .min(self.max_backoff_ms);
+        let jitter = RandomState::new().build_hasher().finish() % (base / 2 + 1);
+        Some(Duration::from_millis(base / 2 + jitter))
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimit {
+    pub requests_per_minute: Option<f64>,
+    pub burst: Option<u32>,
+}
+
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+#[derive(Clone, Default)]
+pub struct RateLimiter(Option<Arc<Mutex<TokenBucket>>>);
+
+impl RateLimiter {
+    pub fn new(limit: &RateLimit) -> Self {
+        let Some(per_minute) = limit.requests_per_minute.filter(|per_minute| *per_minute > 0.0) else {
+            return Self(None);
+        };
+        let capacity = limit.burst.unwrap_or(1).max(1) as f64;
+        Self(Some(Arc::new(Mutex::new(TokenBucket {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: per_minute / 60.0,
+            last_refill: Instant::now(),
+        }))))
+    }
+
+    pub fn acquire(&self) {
+        let Some(bucket) = &self.0 else {
+            return;
+        };
+        let wait = {
+            let mut bucket = bucket.lock().unwrap();
+            let now = Instant::now();
+            let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * bucket.refill_per_sec).min(bucket.capacity);
+            bucket.last_refill = now;
+            bucket.tokens -= 1.0;
+            if bucket.tokens >= 0.0 {
+                Duration::ZERO
+            } else {
+                Duration::from_secs_f64(-bucket.tokens / bucket.refill_per_sec)
+            }
+        };
+        thread::sleep(wait);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RetryEvent {
+    pub attempt: u32,
+    pub delay: Duration,
+    pub error: ProviderError,
+}
+
+pub fn is_retryable(error: &ProviderError) -> bool {
+    matches!(error.kind, ErrorKind::Quota | ErrorKind::Server | ErrorKind::Timeout | ErrorKind::Connection)
+        && error.status != Some(402)
+}
+
+pub fn with_retry<T>(
+    policy: &RetryPolicy,
+    limiter: &RateLimiter,
+    retryable: impl Fn(&ProviderError) -> bool,
+    mut on_retry: impl FnMut(&RetryEvent),
+    mut request: impl FnMut() -> Result<T, ProviderError>,
+) -> Result<T, ProviderError> {
+    let mut attempt = 0;
+    loop {
+        limiter.acquire();
+        match request() {
+            Err(error) if attempt < policy.max_retries && retryable(&error) => {
+                let Some(delay) = policy.backoff(attempt, error.retry_after) else {
+                    return Err(error);
+                };
+                attempt += 1;
+                on_retry(&RetryEvent { attempt, delay, error });
+                thread::sleep(delay);
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_retryable, with_retry, RateLimit, RateLimiter, RetryPolicy};
+    use crate::provider::{ErrorKind, ProviderError};
+    use std::time::{Duration, Instant};
+
+    fn rate_limited(retry_after: Option<Duration>) -> ProviderError {
+        ProviderError { retry_after, ..ProviderError::new(ErrorKind::Quota, Some(429), "slow down".to_string()) }
+    }
+
+    #[test]
+    fn grows_backoff_with_jitter_up_to_the_maximum() {
+        let policy = RetryPolicy { max_retries: 3, initial_backoff_ms: 500, max_backoff_ms: 4_000 };
+        for _ in 0..50 {
+            for (attempt, base) in [(0, 500), (1, 1_000), (2, 2_000), (3, 4_000), (10, 4_000)] {
+                let delay = policy.backoff(attempt, None).unwrap().as_millis() as u64;
+                assert!((base / 2..=base).contains(&delay), "attempt {} waited {}ms", attempt, delay);
+            }
+        }
+    }
+
+    #[test]
+    fn honours_retry_after_exactly_or_gives_up() {
+        let policy = RetryPolicy { max_retries: 3, initial_backoff_ms: 500, max_backoff_ms: 30_000 };
+        assert_eq!(policy.backoff(0, Some(Duration::from_secs(7))), Some(Duration::from_secs(7)));
+        assert_eq!(policy.backoff(2, Some(Duration::from_secs(30))), Some(Duration::from_secs(30)));
+        assert_eq!(policy.backoff(0, Some(Duration::from_secs(31))), None);
+    }
+
+    #[test]
+    fn returns_rate_limit_error_when_retry_after_exceeds_maximum() {
+        let policy = RetryPolicy { max_retries: 3, initial_backoff_ms: 1, max_backoff_ms: 10 };
+        let mut attempts = 0;
+        let mut retries = Vec::new();
+        let result: Result<(), _> = with_retry(&policy, &RateLimiter::default(), is_retryable, |event| retries.push(event.delay), || {
+            attempts += 1;
+            Err(rate_limited(Some(Duration::from_millis(if attempts == 1 { 5 } else { 60_000 }))))
+        });
+        assert_eq!(result.unwrap_err().status, Some(429));
+        assert_eq!(attempts, 2);
+        assert_eq!(retries, vec![Duration::from_millis(5)]);
+    }
+
+    #[test]
+    fn stops_after_max_retries_and_skips_permanent_errors() {
+        let policy = RetryPolicy { max_retries: 2, initial_backoff_ms: 1, max_backoff_ms: 2 };
+        let mut attempts = 0;
+        let mut retries = Vec::new();
+        let result: Result<(), _> = with_retry(&policy, &RateLimiter::default(), is_retryable, |event| retries.push(event.attempt), || {
+            attempts += 1;
+            Err(rate_limited(None))
+        });
+        assert_eq!(result.unwrap_err().status, Some(429));
+        assert_eq!((attempts, retries), (3, vec![1, 2]));
+
+        for (kind, status) in [(ErrorKind::BadKey, Some(401)), (ErrorKind::Quota, Some(402))] {
+            attempts = 0;
+            let result: Result<(), _> = with_retry(&policy, &RateLimiter::default(), is_retryable, |_| {}, || {
+                attempts += 1;
+                Err(ProviderError::new(kind, status, "no retry".to_string()))
+            });
+            assert!(result.is_err());
+            assert_eq!(attempts, 1);
+        }
+    }
+
+    #[test]
+    fn waits_for_the_rate_limit_after_the_burst() {
+        let limiter = RateLimiter::new(&RateLimit { requests_per_minute: Some(1_200.0), burst: Some(2) });
+        let started = Instant::now();
+        limiter.acquire();
+        limiter.acquire();
+        assert!(started.elapsed() < Duration::from_millis(40));
+        limiter.acquire();
+        assert!(started.elapsed() >= Duration::from_millis(40));
+    }
+}
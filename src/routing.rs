@@ -0,0 +1,160 @@
+use crate::provider::{ErrorKind, ProviderError};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    #[default]
+    Priority,
+    WeightedRoundRobin,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FallbackOn {
+    RateLimit,
+    ServerError,
+    Timeout,
+    Connection,
+    Any,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RouteTarget {
+    pub provider: String,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RouteRule {
+    pub min_prompt_tokens: u64,
+    pub provider: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Route {
+    pub name: String,
+    #[serde(default)]
+    pub strategy: Strategy,
+    pub targets: Vec<RouteTarget>,
+    #[serde(default = "default_fallback_on")]
+    pub fallback_on: Vec<FallbackOn>,
+    #[serde(default)]
+    pub rules: Vec<RouteRule>,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+fn default_fallback_on() -> Vec<FallbackOn> {
+    vec![FallbackOn::RateLimit, FallbackOn::ServerError, FallbackOn::Timeout]
+}
+
+pub fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(4)
+}
+
+impl Route {
+    pub fn plan(&self, prompt_tokens: u64, sequence: u64) -> Vec<String> {
+        let mut plan: Vec<String> = Vec::new();
+        if let Some(rule) = self
+            .rules
+            .iter()
+            .filter(|rule| prompt_tokens >= rule.min_prompt_tokens)
+            .max_by_key(|rule| rule.min_prompt_tokens)
+        {
+            plan.push(rule.provider.clone());
+        }
+
+        let mut targets: Vec<&RouteTarget> = self.targets.iter().collect();
+        if self.strategy == Strategy::WeightedRoundRobin {
+            let total: u64 = targets.iter().map(|target| target.weight as u64).sum();
+            if total > 0 {
+                let mut slot = sequence % total;
+                let first = targets
+                    .iter()
+                    .position(|target| {
+                        if slot < target.weight as u64 {
+                            true
+                        } else {
+                            slot -= target.weight as u64;
+                            false
+                        }
+                    })
+                    .unwrap_or_default();
+                targets.rotate_left(first);
+            }
+        }
+        for target in targets {
+            if !plan.contains(&target.provider) {
+                plan.push(target.provider.clone());
+            }
+        }
+        plan
+    }
+
+    pub fn should_fall_back(&self, error: &ProviderError) -> bool {
+        self.fallback_on.iter().any(|condition| match condition {
+            FallbackOn::RateLimit => error.kind == ErrorKind::Quota,
+            FallbackOn::ServerError => error.kind == ErrorKind::Server,
+            FallbackOn::Timeout => error.kind == ErrorKind::Timeout,
+            FallbackOn::Connection => matches!(error.kind, ErrorKind::Connection | ErrorKind::Dns | ErrorKind::Tls),
+            FallbackOn::Any => true,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FallbackOn, Route, RouteRule, RouteTarget, Strategy};
+    use crate::provider::{ErrorKind, ProviderError};
+
+    fn route(strategy: Strategy, targets: &[(&str, u32)]) -> Route {
+        Route {
+            name: "default".to_string(),
+            strategy,
+            targets: targets.iter().map(|(provider, weight)| RouteTarget { provider: provider.to_string(), weight: *weight }).collect(),
+            fallback_on: vec![FallbackOn::RateLimit, FallbackOn::ServerError, FallbackOn::Timeout],
+            rules: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn plans_priority_targets_after_matching_rule() {
+        let mut route = route(Strategy::Priority, &[("primary", 1), ("backup", 1), ("long", 1)]);
+        route.rules = vec![
+            RouteRule { min_prompt_tokens: 1_000, provider: "long".to_string() },
+            RouteRule { min_prompt_tokens: 10_000, provider: "huge".to_string() },
+        ];
+        assert_eq!(route.plan(10, 0), vec!["primary", "backup", "long"]);
+        assert_eq!(route.plan(5_000, 7), vec!["long", "primary", "backup"]);
+        assert_eq!(route.plan(20_000, 0), vec!["huge", "primary", "backup", "long"]);
+    }
+
+    #[test]
+    fn distributes_weighted_round_robin_by_weight() {
+        let route = route(Strategy::WeightedRoundRobin, &[("a", 3), ("b", 1), ("c", 0)]);
+        let firsts: Vec<String> = (0..8).map(|sequence| route.plan(0, sequence)[0].clone()).collect();
+        assert_eq!(firsts, ["a", "a", "a", "b", "a", "a", "a", "b"]);
+        assert_eq!(route.plan(0, 3), vec!["b", "c", "a"]);
+    }
+
+    #[test]
+    fn falls_back_only_on_configured_errors() {
+        let route = route(Strategy::Priority, &[("a", 1)]);
+        let error = |kind, status| ProviderError::new(kind, status, String::new());
+        assert!(route.should_fall_back(&error(ErrorKind::Quota, Some(429))));
+        assert!(route.should_fall_back(&error(ErrorKind::Server, Some(503))));
+        assert!(route.should_fall_back(&error(ErrorKind::Timeout, None)));
+        assert!(!route.should_fall_back(&error(ErrorKind::Connection, None)));
+        assert!(!route.should_fall_back(&error(ErrorKind::BadKey, Some(401))));
+
+        let connection = Route { fallback_on: vec![FallbackOn::Connection], ..route.clone() };
+        assert!(connection.should_fall_back(&error(ErrorKind::Tls, None)));
+        assert!(!connection.should_fall_back(&error(ErrorKind::Quota, Some(429))));
+        let any = Route { fallback_on: vec![FallbackOn::Any], ..route };
+        assert!(any.should_fall_back(&error(ErrorKind::BadKey, Some(401))));
+    }
+}
@@ -0,0 +1,205 @@
+use crate::config;
+use crate::env::Env;
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, Generate, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const VAULT_MAGIC: &[u8; 4] = b"AIV1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+pub fn is_reference(value: &str) -> bool {
+    value.starts_with("env:") || value.starts_with("cmd:") || value.starts_with("vault:") || value.contains("${")
+}
+
+pub fn mask(value: &str) -> String {
+    if is_reference(value) {
+        return value.to_string();
+    }
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 8 {
+        return "•".repeat(chars.len());
+    }
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}{}", "•".repeat(chars.len() - 4), tail)
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Resolved {
+    pub value: String,
+    pub source: String,
+}
+
+impl Resolved {
+    pub fn describe(&self) -> String {
+        if self.value.is_empty() {
+            format!("{} (empty)", self.source)
+        } else {
+            format!("{} ({})", self.source, mask(&self.value))
+        }
+    }
+}
+
+pub fn resolve(value: &str, vault: Option<&Vault>, env: &Env) -> Result<Resolved, String> {
+    if let Some(var) = value.strip_prefix("env:") {
+        let (resolved, origin) = env.get(var).ok_or_else(|| format!("environment variable {} is not set", var))?;
+        Ok(Resolved { value: resolved, source: format!("{} {}", origin, var) })
+    } else if let Some(command) = value.strip_prefix("cmd:") {
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .output()
+            .map_err(|e| format!("failed to run secret command: {}", e))?;
+        if !output.status.success() {
+            return Err(format!("secret command exited with {}", output.status));
+        }
+        let resolved = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok(Resolved { value: resolved, source: "cmd".to_string() })
+    } else if let Some(name) = value.strip_prefix("vault:") {
+        let vault = vault.ok_or_else(|| "vault is locked".to_string())?;
+        let secret = vault.get(name).ok_or_else(|| format!("no secret {} in vault", name))?;
+        Ok(Resolved { value: secret.to_string(), source: format!("vault {}", name) })
+    } else {
+        Ok(Resolved { value: value.to_string(), source: "config".to_string() })
+    }
+}
+
+pub struct Vault {
+    path: PathBuf,
+    salt: [u8; SALT_LEN],
+    key: Key,
+    secrets: BTreeMap<String, String>,
+}
+
+impl Vault {
+    pub fn create(path: &Path, passphrase: &str) -> io::Result<Self> {
+        if path.exists() {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, "vault already exists"));
+        }
+        let salt = <[u8; SALT_LEN]>::generate();
+        let vault = Self { path: path.to_path_buf(), salt, key: derive_key(passphrase, &salt)?, secrets: BTreeMap::new() };
+        vault.save()?;
+        Ok(vault)
+    }
+
+    pub fn unlock(path: &Path, passphrase: &str) -> io::Result<Self> {
+        let data = fs::read(path)?;
+        if data.len() < VAULT_MAGIC.len() + SALT_LEN + NONCE_LEN || &data[..VAULT_MAGIC.len()] != VAULT_MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a vault file"));
+        }
+        let (salt, rest) = data[VAULT_MAGIC.len()..].split_at(SALT_LEN);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        let salt: [u8; SALT_LEN] = salt.try_into().unwrap();
+        let key = derive_key(passphrase, &salt)?;
+        let nonce = Nonce::try_from(nonce).unwrap();
+        let plaintext = ChaCha20Poly1305::new(&key)
+            .decrypt(&nonce, ciphertext)
+            .map_err(|_| io::Error::new(io::ErrorKind::PermissionDenied, "wrong vault passphrase"))?;
+        let secrets = serde_json::from_slice(&plaintext)?;
+
+        Ok(Self { path: path.to_path_buf(), salt, key, secrets })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.secrets.get(name).map(String::as_str)
+    }
+
+    pub fn set(&mut self, name: &str, secret: &str) -> io::Result<()> {
+        self.secrets.insert(name.to_string(), secret.to_string());
+        self.save()
+    }
+
+    pub fn remove(&mut self, name: &str) -> io::Result<()> {
+        if self.secrets.remove(name).is_some() {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let plaintext = serde_json::to_vec(&self.secrets)?;
+        let nonce = Nonce::generate();
+        let ciphertext = ChaCha20Poly1305::new(&self.key)
+            .encrypt(&nonce, plaintext.as_slice())
+            .map_err(|_| io::Error::other("failed to encrypt vault"))?;
+
+        let mut data = Vec::with_capacity(VAULT_MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+        data.extend_from_slice(VAULT_MAGIC);
+        data.extend_from_slice(&self.salt);
+        data.extend_from_slice(&nonce);
+        data.extend_from_slice(&ciphertext);
+
+        config::write_atomic(&self.path, &data, 0o600)
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> io::Result<Key> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    Ok(Key::from(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_reference, mask, resolve, Vault};
+    use crate::env::Env;
+    use crate::snapshot;
+    use std::io;
+
+    fn value(reference: &str, vault: Option<&Vault>) -> Result<String, String> {
+        resolve(reference, vault, &Env::default()).map(|resolved| resolved.value)
+    }
+
+    #[test]
+    fn stores_and_reopens_secrets() {
+        let path = snapshot::temp_dir("vault-round-trip").join("vault.bin");
+        let mut vault = Vault::create(&path, "correct horse").unwrap();
+        vault.set("Work", "sk-work").unwrap();
+        vault.set("Home", "sk-home").unwrap();
+        vault.remove("Home").unwrap();
+
+        let vault = Vault::unlock(&path, "correct horse").unwrap();
+        assert_eq!(vault.get("Work"), Some("sk-work"));
+        assert_eq!(vault.get("Home"), None);
+        assert_eq!(Vault::create(&path, "again").err().map(|e| e.kind()), Some(io::ErrorKind::AlreadyExists));
+        assert!(!path.with_file_name("vault.bin.tmp").exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        assert_eq!(value("vault:Work", Some(&vault)), Ok("sk-work".to_string()));
+        assert_eq!(value("vault:Home", Some(&vault)), Err("no secret Home in vault".to_string()));
+        assert_eq!(value("vault:Work", None), Err("vault is locked".to_string()));
+    }
+
+    #[test]
+    fn rejects_wrong_passphrase_and_missing_vault() {
+        let dir = snapshot::temp_dir("vault-wrong");
+        Vault::create(&dir.join("vault.bin"), "correct horse").unwrap();
+        let wrong = Vault::unlock(&dir.join("vault.bin"), "battery staple").err().unwrap();
+        assert_eq!(wrong.kind(), io::ErrorKind::PermissionDenied);
+        let missing = Vault::unlock(&dir.join("missing.bin"), "correct horse").err().unwrap();
+        assert_eq!(missing.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn resolves_and_masks_references() {
+        assert!(is_reference("vault:Work"));
+        assert!(is_reference("env:OPENAI_API_KEY"));
+        assert!(is_reference("${OPENAI_API_KEY}"));
+        assert!(!is_reference("sk-plain"));
+        assert_eq!(value("cmd:echo sk-from-command", None), Ok("sk-from-command".to_string()));
+        assert_eq!(value("sk-plain", None), Ok("sk-plain".to_string()));
+        assert_eq!(mask("sk-0123456789"), "•••••••••6789");
+        assert_eq!(mask("short"), "•••••");
+        assert_eq!(mask("env:KEY"), "env:KEY");
+    }
+}
@@ -0,0 +1,161 @@
+use crate::app::App;
+use crate::env::Env;
+use crate::keymap::{KeyChord, Keymap, Resolution};
+use crate::notify::Notifications;
+use crate::store::{ProviderStore, SharedStore};
+use crate::theme::Theme;
+use crate::traits::View;
+use crate::usage::{SharedLedger, UsageLedger};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::backend::TestBackend;
+use ratatui::{Frame, Terminal};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub trait Subject {
+    fn draw(&self, f: &mut Frame, theme: &Theme);
+    fn press(&mut self, key: KeyEvent, keymap: &Keymap, pending: &mut Vec<KeyChord>, notifications: &mut Notifications);
+    fn mouse(&mut self, event: MouseEvent, notifications: &mut Notifications);
+}
+
+impl<V: View> Subject for V {
+    fn draw(&self, f: &mut Frame, theme: &Theme) {
+        self.render(f, f.area(), theme);
+    }
+
+    fn press(&mut self, key: KeyEvent, keymap: &Keymap, pending: &mut Vec<KeyChord>, notifications: &mut Notifications) {
+        let binding = match keymap.feed(pending, &[self.keymap_scope()], key) {
+            Resolution::Pending => return,
+            Resolution::Bound(binding) => Some(binding.action.as_str()),
+            Resolution::Unbound => None,
+        };
+        self.handle_input(key, binding, notifications);
+    }
+
+    fn mouse(&mut self, event: MouseEvent, notifications: &mut Notifications) {
+        self.handle_mouse(event, notifications);
+    }
+}
+
+impl Subject for App {
+    fn draw(&self, f: &mut Frame, _theme: &Theme) {
+        App::draw(self, f);
+    }
+
+    fn press(&mut self, key: KeyEvent, _keymap: &Keymap, _pending: &mut Vec<KeyChord>, _notifications: &mut Notifications) {
+        self.handle_key(key);
+    }
+
+    fn mouse(&mut self, event: MouseEvent, _notifications: &mut Notifications) {
+        self.handle_mouse(event);
+    }
+}
+
+pub struct Harness<S> {
+    pub subject: S,
+    pub notifications: Notifications,
+    pub keymap: Keymap,
+    pub theme: Theme,
+    pending: Vec<KeyChord>,
+    terminal: Terminal<TestBackend>,
+}
+
+impl<S: Subject> Harness<S> {
+    pub fn new(subject: S, width: u16, height: u16) -> Self {
+        Self {
+            subject,
+            notifications: Notifications::default(),
+            keymap: Keymap::default(),
+            theme: Theme::default(),
+            pending: Vec::new(),
+            terminal: Terminal::new(TestBackend::new(width, height)).unwrap(),
+        }
+    }
+
+    pub fn keys(&mut self, keys: &[KeyEvent]) -> &mut Self {
+        for key in keys {
+            self.subject.press(*key, &self.keymap, &mut self.pending, &mut self.notifications);
+        }
+        self
+    }
+
+    pub fn type_text(&mut self, text: &str) -> &mut Self {
+        for c in text.chars() {
+            self.subject.press(key(KeyCode::Char(c)), &self.keymap, &mut self.pending, &mut self.notifications);
+        }
+        self
+    }
+
+    pub fn mouse(&mut self, events: &[MouseEvent]) -> &mut Self {
+        self.render();
+        for event in events {
+            self.subject.mouse(*event, &mut self.notifications);
+        }
+        self
+    }
+
+    pub fn render(&mut self) -> String {
+        let Self { subject, theme, terminal, .. } = self;
+        terminal.draw(|f| subject.draw(f, theme)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let width = buffer.area.width as usize;
+        buffer
+            .content
+            .chunks(width)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>().trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+            + "\n"
+    }
+
+    pub fn assert_snapshot(&mut self, name: &str) -> &mut Self {
+        let actual = self.render();
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/snapshots").join(format!("{}.snap", name));
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, actual).unwrap();
+            return self;
+        }
+        match fs::read_to_string(&path) {
+            Ok(expected) => assert!(
+                expected == actual,
+                "snapshot {} does not match, rerun with UPDATE_SNAPSHOTS=1 to accept\n--- expected\n{}--- actual\n{}",
+                name,
+                expected,
+                actual
+            ),
+            Err(e) => panic!("snapshot {} is missing ({}), rerun with UPDATE_SNAPSHOTS=1 to create it\n--- actual\n{}", name, e, actual),
+        }
+        self
+    }
+}
+
+pub fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+pub fn ctrl(c: char) -> KeyEvent {
+    KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+}
+
+pub fn mouse(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+    MouseEvent { kind, column, row, modifiers: KeyModifiers::NONE }
+}
+
+pub fn click(column: u16, row: u16) -> MouseEvent {
+    mouse(MouseEventKind::Down(MouseButton::Left), column, row)
+}
+
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ai-snapshot-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+pub fn store(name: &str) -> (SharedStore, SharedLedger) {
+    let dir = temp_dir(name);
+    let store = ProviderStore::load(dir.join("providers.yaml"), dir.join("vault.bin"), Env::default()).shared();
+    let ledger = UsageLedger::load(&dir.join("usage.yaml"), dir.join("usage.jsonl")).shared();
+    (store, ledger)
+}
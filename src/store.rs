@@ -0,0 +1,287 @@
+use crate::chat::{Candidate, Dispatch};
+use crate::config::{ProviderEntry, ProvidersConfig};
+use crate::env::Env;
+use crate::provider::{ProviderInstance, ProviderSettings, ResolvedSettings};
+use crate::retry::RateLimiter;
+use crate::routing::{self, Route};
+use crate::secret::{self, Vault};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+pub type SharedStore = Rc<RefCell<ProviderStore>>;
+
+pub struct ProviderStore {
+    pub instances: BTreeMap<String, ProviderInstance>,
+    pub settings: BTreeMap<String, ProviderSettings>,
+    pub vault: Option<Vault>,
+    pub vault_error: Option<String>,
+    pub config_error: Option<String>,
+    pub env: Env,
+    pub routes: BTreeMap<String, Route>,
+    route_sequence: HashMap<String, u64>,
+    limiters: HashMap<String, RateLimiter>,
+    config_path: PathBuf,
+    vault_path: PathBuf,
+}
+
+impl ProviderStore {
+    pub fn load(config_path: PathBuf, vault_path: PathBuf, env: Env) -> Self {
+        let mut store = Self {
+            instances: BTreeMap::new(),
+            settings: BTreeMap::new(),
+            vault: None,
+            vault_error: None,
+            config_error: None,
+            env,
+            routes: BTreeMap::new(),
+            route_sequence: HashMap::new(),
+            limiters: HashMap::new(),
+            config_path,
+            vault_path,
+        };
+
+        let config = ProvidersConfig::load(&store.config_path).unwrap_or_else(|e| {
+            store.config_error = Some(format!("cannot read {}: {}", store.config_path.display(), e));
+            ProvidersConfig::default()
+        });
+        for entry in config.providers {
+            store.instances.insert(
+                entry.name.clone(),
+                ProviderInstance { name: entry.name.clone(), provider_type: entry.provider_type },
+            );
+            store.settings.insert(entry.name, entry.settings);
+        }
+        for route in config.routes {
+            store.routes.insert(route.name.clone(), route);
+        }
+        if let Ok(passphrase) = std::env::var("AI_VAULT_PASSPHRASE") {
+            if store.vault_exists() {
+                if let Err(e) = store.unlock_vault(&passphrase) {
+                    store.vault_error = Some(format!("AI_VAULT_PASSPHRASE did not unlock the vault: {}", e));
+                }
+            }
+        }
+        store
+    }
+
+    pub fn shared(self) -> SharedStore {
+        Rc::new(RefCell::new(self))
+    }
+
+    pub fn vault_exists(&self) -> bool {
+        self.vault_path.exists()
+    }
+
+    pub fn create_vault(&mut self, passphrase: &str) -> io::Result<()> {
+        self.vault = Some(Vault::create(&self.vault_path, passphrase)?);
+        self.vault_error = None;
+        Ok(())
+    }
+
+    pub fn unlock_vault(&mut self, passphrase: &str) -> io::Result<()> {
+        self.vault = Some(Vault::unlock(&self.vault_path, passphrase)?);
+        self.vault_error = None;
+        Ok(())
+    }
+
+    pub fn vault_secret(&self, name: &str) -> Option<&str> {
+        let secret = self.settings.get(name)?.api_key.as_deref()?.strip_prefix("vault:")?;
+        let shared = self
+            .settings
+            .iter()
+            .any(|(other, settings)| other != name && settings.api_key.as_deref() == Some(&format!("vault:{}", secret)));
+        (!shared).then_some(secret)
+    }
+
+    pub fn plaintext_keys(&self) -> Vec<String> {
+        self.settings
+            .iter()
+            .filter(|(_, settings)| settings.api_key.as_deref().is_some_and(|key| !key.is_empty() && !secret::is_reference(key)))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    pub fn new_name(&self) -> String {
+        (self.instances.len() + 1..)
+            .map(|n| format!("Provider{}", n))
+            .find(|name| !self.instances.contains_key(name))
+            .unwrap()
+    }
+
+    pub fn update(&mut self, instance: ProviderInstance, settings: ProviderSettings) {
+        self.limiters.remove(&instance.name);
+        self.settings.insert(instance.name.clone(), settings);
+        self.instances.insert(instance.name.clone(), instance);
+    }
+
+    pub fn remove(&mut self, name: &str) -> io::Result<()> {
+        if let Some(secret) = self.vault_secret(name).map(str::to_string) {
+            let vault = self
+                .vault
+                .as_mut()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::PermissionDenied, "vault is locked"))?;
+            vault.remove(&secret)?;
+        }
+        self.limiters.remove(name);
+        self.settings.remove(name);
+        self.instances.remove(name);
+        Ok(())
+    }
+
+    pub fn limiter(&mut self, name: &str) -> RateLimiter {
+        let rate_limit = self.settings.get(name).map(|settings| settings.rate_limit.clone()).unwrap_or_default();
+        self.limiters.entry(name.to_string()).or_insert_with(|| RateLimiter::new(&rate_limit)).clone()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.instances.keys().cloned().collect()
+    }
+
+    pub fn targets(&self) -> Vec<String> {
+        self.routes
+            .keys()
+            .map(|name| format!("route:{}", name))
+            .chain(self.instances.keys().cloned())
+            .collect()
+    }
+
+    pub fn plan(&mut self, target: &str, prompt: &str) -> (Option<Route>, Vec<String>) {
+        let Some(route) = target.strip_prefix("route:").and_then(|name| self.routes.get(name)).cloned() else {
+            return (None, vec![target.to_string()]);
+        };
+        let sequence = self.route_sequence.entry(route.name.clone()).or_default();
+        let plan = route.plan(routing::estimate_tokens(prompt), *sequence);
+        *sequence += 1;
+        (Some(route), plan)
+    }
+
+    pub fn prepare(&mut self, target: &str, prompt: &str, model_override: Option<&str>) -> Dispatch {
+        let (route, plan) = self.plan(target, prompt);
+        let candidates = plan
+            .into_iter()
+            .map(|name| {
+                let limiter = self.limiter(&name);
+                let resolved = self.resolve(&name).map(|(instance, mut settings)| {
+                    if let Some(model) = model_override.filter(|_| route.is_none()) {
+                        settings.model = model.to_string();
+                    }
+                    (instance, settings)
+                });
+                Candidate { name, resolved, limiter }
+            })
+            .collect();
+        Dispatch { target: target.to_string(), route, candidates }
+    }
+
+    pub fn resolve(&self, name: &str) -> Result<(ProviderInstance, ResolvedSettings), String> {
+        let instance = self.instances.get(name).ok_or_else(|| format!("unknown provider {}", name))?;
+        let settings = self.settings.get(name).cloned().unwrap_or_default();
+        let resolved = settings.resolve(&instance.provider_type, self.vault.as_ref(), &self.env)?;
+        Ok((instance.clone(), resolved))
+    }
+
+    pub fn save(&mut self) -> io::Result<()> {
+        if let Some(error) = &self.config_error {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{}, fix it before saving", error)));
+        }
+        for name in self.plaintext_keys() {
+            let vault = self.vault.as_mut().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::PermissionDenied, format!("vault is locked, cannot store the API key of {}", name))
+            })?;
+            let settings = self.settings.get_mut(&name).unwrap();
+            vault.set(&name, settings.api_key.as_deref().unwrap_or_default())?;
+            settings.api_key = Some(format!("vault:{}", name));
+        }
+        let config = ProvidersConfig {
+            providers: self
+                .instances
+                .values()
+                .map(|instance| ProviderEntry {
+                    name: instance.name.clone(),
+                    provider_type: instance.provider_type.clone(),
+                    settings: self.settings.get(&instance.name).cloned().unwrap_or_default(),
+                })
+                .collect(),
+            routes: self.routes.values().cloned().collect(),
+        };
+        config.save(&self.config_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProviderStore;
+    use crate::env::Env;
+    use crate::provider::{ProviderInstance, ProviderSettings, ProviderType};
+    use crate::routing::{Route, RouteTarget, Strategy};
+    use crate::snapshot;
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+
+    fn load(dir: &Path) -> ProviderStore {
+        ProviderStore::load(dir.join("providers.yaml"), dir.join("vault.bin"), Env::default())
+    }
+
+    #[test]
+    fn saves_and_reloads_providers() {
+        let dir = snapshot::temp_dir("store-round-trip");
+        let mut store = load(&dir);
+        assert!(store.names().is_empty());
+        for (name, provider_type, key) in [("Work", ProviderType::OpenAI, "vault:Work"), ("Local", ProviderType::Ollama, "")] {
+            store.instances.insert(name.to_string(), ProviderInstance { name: name.to_string(), provider_type });
+            store.settings.insert(name.to_string(), ProviderSettings { api_key: Some(key.to_string()), ..ProviderSettings::default() });
+        }
+        store.save().unwrap();
+
+        let store = load(&dir);
+        assert_eq!(store.names(), ["Local", "Work"]);
+        let (instance, settings) = store.resolve("Local").unwrap();
+        assert!(matches!(instance.provider_type, ProviderType::Ollama));
+        assert_eq!(settings.entry_point(), "http://localhost:11434");
+        assert_eq!(store.resolve("Work").unwrap_err(), "vault is locked");
+        assert_eq!(store.resolve("Missing").unwrap_err(), "unknown provider Missing");
+    }
+
+    #[test]
+    fn saves_routes_and_rotates_weighted_plans() {
+        let dir = snapshot::temp_dir("store-routes");
+        let mut store = load(&dir);
+        store.routes.insert(
+            "fast".to_string(),
+            Route {
+                name: "fast".to_string(),
+                strategy: Strategy::WeightedRoundRobin,
+                targets: ["Work", "Local"].map(|provider| RouteTarget { provider: provider.to_string(), weight: 1 }).to_vec(),
+                fallback_on: Vec::new(),
+                rules: Vec::new(),
+            },
+        );
+        store.instances.insert("Work".to_string(), ProviderInstance { name: "Work".to_string(), provider_type: ProviderType::OpenAI });
+        store.save().unwrap();
+
+        let mut store = load(&dir);
+        assert_eq!(store.targets(), ["route:fast", "Work"]);
+        assert_eq!(store.plan("route:fast", "hi").1, ["Work", "Local"]);
+        assert_eq!(store.plan("route:fast", "hi").1, ["Local", "Work"]);
+        let (route, plan) = store.plan("Work", "hi");
+        assert!(route.is_none());
+        assert_eq!(plan, ["Work"]);
+    }
+
+    #[test]
+    fn keeps_an_unreadable_config_untouched() {
+        let dir = snapshot::temp_dir("store-invalid-config");
+        fs::write(dir.join("providers.yaml"), "providers: [unterminated").unwrap();
+        let mut store = load(&dir);
+        assert!(store.names().is_empty());
+        assert!(store.config_error.as_deref().unwrap().starts_with("cannot read "), "{:?}", store.config_error);
+
+        store.instances.insert("Local".to_string(), ProviderInstance { name: "Local".to_string(), provider_type: ProviderType::Ollama });
+        assert_eq!(store.save().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(fs::read_to_string(dir.join("providers.yaml")).unwrap(), "providers: [unterminated");
+    }
+}
@@ -0,0 +1,161 @@
+use crate::config;
+use crate::logging;
+use chrono::Local;
+use crossterm::{
+    cursor::Show,
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::backend::CrosstermBackend;
+use ratatui::Terminal;
+use serde_json::json;
+use signal_hook::consts::{SIGINT, SIGTERM};
+use std::backtrace::Backtrace;
+use std::fs;
+use std::io::{self, Stdout};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+pub type Tui = Terminal<CrosstermBackend<Stdout>>;
+
+pub fn enter() -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)
+}
+
+pub fn restore() {
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, DisableBracketedPaste, Show);
+}
+
+pub fn init() -> io::Result<Tui> {
+    install_panic_hook();
+    enter()?;
+    Terminal::new(CrosstermBackend::new(io::stdout()))
+}
+
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let report = write_crash_report(&config::crash_dir(), &info.to_string(), &Backtrace::force_capture());
+        let path = report.as_ref().map(|path| path.display().to_string()).unwrap_or_default();
+        logging::try_error("panic", &info.to_string(), vec![("report", json!(path))]);
+        if std::thread::current().name() != Some("main") {
+            return;
+        }
+        restore();
+        default_hook(info);
+        match report {
+            Ok(path) => eprintln!("Crash report written to {}", path.display()),
+            Err(e) => eprintln!("Failed to write crash report: {}", e),
+        }
+    }));
+}
+
+pub fn write_crash_report(dir: &Path, message: &str, backtrace: &Backtrace) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let now = Local::now();
+    let path = dir.join(format!("crash-{}.log", now.format("%Y%m%d-%H%M%S-%3f")));
+    let thread = std::thread::current();
+    let report = format!(
+        "time: {}\nversion: {}\nthread: {}\n\n{}\n\nbacktrace:\n{}\n",
+        now.to_rfc3339(),
+        env!("CARGO_PKG_VERSION"),
+        thread.name().unwrap_or("unnamed"),
+        message,
+        backtrace
+    );
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+pub struct Signals {
+    terminate: Arc<AtomicBool>,
+    suspend: Arc<AtomicBool>,
+}
+
+impl Signals {
+    fn new() -> Self {
+        Self { terminate: Arc::new(AtomicBool::new(false)), suspend: Arc::new(AtomicBool::new(false)) }
+    }
+
+    pub fn register() -> io::Result<Self> {
+        let signals = Self::new();
+        for signal in [SIGINT, SIGTERM] {
+            signal_hook::flag::register(signal, signals.terminate.clone())?;
+        }
+        #[cfg(unix)]
+        signal_hook::flag::register(signal_hook::consts::SIGTSTP, signals.suspend.clone())?;
+        Ok(signals)
+    }
+
+    pub fn terminate_requested(&self) -> bool {
+        self.terminate.load(Ordering::Relaxed)
+    }
+
+    pub fn take_suspend(&self) -> bool {
+        self.suspend.swap(false, Ordering::Relaxed)
+    }
+
+    /// Runs a child that owns the terminal, such as `$EDITOR`. Ctrl-C or Ctrl-Z pressed in the
+    /// child reach us too, so signals delivered meanwhile are dropped instead of acted on later.
+    pub fn detached<T>(&self, run: impl FnOnce() -> T) -> T {
+        let result = run();
+        self.terminate.store(false, Ordering::Relaxed);
+        self.suspend.store(false, Ordering::Relaxed);
+        result
+    }
+}
+
+pub fn suspended<T>(terminal: &mut Tui, run: impl FnOnce() -> T) -> io::Result<T> {
+    restore();
+    let result = run();
+    enter()?;
+    terminal.clear()?;
+    Ok(result)
+}
+
+pub fn suspend(terminal: &mut Tui) -> io::Result<()> {
+    suspended(terminal, || -> io::Result<()> {
+        #[cfg(unix)]
+        signal_hook::low_level::emulate_default_handler(signal_hook::consts::SIGTSTP)?;
+        Ok(())
+    })?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_crash_report, Signals};
+    use crate::snapshot;
+    use std::backtrace::Backtrace;
+    use std::fs;
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    fn writes_crash_report_with_backtrace() {
+        let dir = snapshot::temp_dir("crash").join("crashes");
+        let path = write_crash_report(&dir, "panicked at src/app.rs:1:1:\nboom", &Backtrace::force_capture()).unwrap();
+        let report = fs::read_to_string(&path).unwrap();
+        assert!(path.file_name().unwrap().to_string_lossy().starts_with("crash-"));
+        assert!(report.contains("version: 0.1.0"));
+        assert!(report.contains("boom"));
+        assert!(report.contains("backtrace:\n"));
+        assert!(report.contains("writes_crash_report_with_backtrace"));
+    }
+
+    #[test]
+    fn drops_signals_delivered_to_detached_children() {
+        let signals = Signals::new();
+        let edited = signals.detached(|| {
+            signals.terminate.store(true, Ordering::Relaxed);
+            signals.suspend.store(true, Ordering::Relaxed);
+            "edited"
+        });
+        assert_eq!(edited, "edited");
+        assert!(!signals.terminate_requested());
+        assert!(!signals.take_suspend());
+    }
+}
@@ -0,0 +1,329 @@
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+pub const BUILT_IN: [&str; 3] = ["dark", "light", "high-contrast"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub text: Style,
+    pub border: Style,
+    pub header_active: Style,
+    pub header_inactive: Style,
+    pub highlight: Style,
+    pub error: Style,
+    pub info: Style,
+    pub success: Style,
+    pub warning: Style,
+    pub muted: Style,
+    pub accent: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        let bold = Modifier::BOLD;
+        Self {
+            name: "dark".to_string(),
+            text: Style::default(),
+            border: Style::default(),
+            header_active: Style::default().fg(Color::Green).add_modifier(bold),
+            header_inactive: Style::default().fg(Color::Yellow).add_modifier(bold),
+            highlight: Style::default().fg(Color::Yellow).add_modifier(bold),
+            error: Style::default().fg(Color::Red),
+            info: Style::default().fg(Color::White),
+            success: Style::default().fg(Color::Green),
+            warning: Style::default().fg(Color::Yellow),
+            muted: Style::default().fg(Color::DarkGray),
+            accent: Style::default().fg(Color::Cyan),
+        }
+    }
+
+    pub fn light() -> Self {
+        let bold = Modifier::BOLD;
+        Self {
+            name: "light".to_string(),
+            text: Style::default().fg(Color::Black),
+            border: Style::default().fg(Color::Gray),
+            header_active: Style::default().fg(Color::Blue).add_modifier(bold),
+            header_inactive: Style::default().fg(Color::DarkGray).add_modifier(bold),
+            highlight: Style::default().fg(Color::Magenta).add_modifier(bold),
+            error: Style::default().fg(Color::Red),
+            info: Style::default().fg(Color::Black),
+            success: Style::default().fg(Color::Green),
+            warning: Style::default().fg(Color::Rgb(176, 96, 0)),
+            muted: Style::default().fg(Color::Gray),
+            accent: Style::default().fg(Color::Blue),
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        let bold = Modifier::BOLD;
+        Self {
+            name: "high-contrast".to_string(),
+            text: Style::default().fg(Color::White),
+            border: Style::default().fg(Color::White),
+            header_active: Style::default().fg(Color::Black).bg(Color::White).add_modifier(bold),
+            header_inactive: Style::default().fg(Color::White).add_modifier(bold),
+            highlight: Style::default().fg(Color::Black).bg(Color::LightYellow).add_modifier(bold),
+            error: Style::default().fg(Color::LightRed).add_modifier(bold),
+            info: Style::default().fg(Color::White).add_modifier(bold),
+            success: Style::default().fg(Color::LightGreen).add_modifier(bold),
+            warning: Style::default().fg(Color::LightYellow).add_modifier(bold),
+            muted: Style::default().fg(Color::White),
+            accent: Style::default().fg(Color::LightCyan).add_modifier(bold),
+        }
+    }
+
+    pub fn built_in(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
+    pub fn block<'a>(&self, title: impl Into<ratatui::text::Line<'a>>) -> Block<'a> {
+        Block::default().borders(Borders::ALL).border_style(self.border).title(title)
+    }
+
+    fn style_mut(&mut self, name: &str) -> Option<&mut Style> {
+        Some(match name {
+            "text" => &mut self.text,
+            "border" => &mut self.border,
+            "header_active" => &mut self.header_active,
+            "header_inactive" => &mut self.header_inactive,
+            "highlight" => &mut self.highlight,
+            "error" => &mut self.error,
+            "info" => &mut self.info,
+            "success" => &mut self.success,
+            "warning" => &mut self.warning,
+            "muted" => &mut self.muted,
+            "accent" => &mut self.accent,
+            _ => return None,
+        })
+    }
+
+    fn from_file(file: ThemeFile, warnings: &mut Vec<String>) -> Self {
+        let mut theme = Self::built_in(&file.base).unwrap_or_else(|| {
+            warnings.push(format!("theme {}: unknown base {}", file.name, file.base));
+            Self::dark()
+        });
+        theme.name = file.name.clone();
+        for (name, spec) in file.styles {
+            let Some(style) = theme.style_mut(&name) else {
+                warnings.push(format!("theme {}: unknown style {}", file.name, name));
+                continue;
+            };
+            match spec.style() {
+                Ok(parsed) => *style = parsed,
+                Err(e) => warnings.push(format!("theme {}: {}: {}", file.name, name, e)),
+            }
+        }
+        theme
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct StyleSpec {
+    #[serde(default)]
+    pub fg: Option<String>,
+    #[serde(default)]
+    pub bg: Option<String>,
+    #[serde(default)]
+    pub bold: bool,
+    #[serde(default)]
+    pub italic: bool,
+    #[serde(default)]
+    pub underline: bool,
+    #[serde(default)]
+    pub reversed: bool,
+}
+
+impl StyleSpec {
+    fn style(&self) -> Result<Style, String> {
+        let color = |value: &Option<String>| {
+            value.as_deref().map(|value| Color::from_str(value).map_err(|_| format!("unknown color {}", value))).transpose()
+        };
+        let mut style = Style::default();
+        if let Some(fg) = color(&self.fg)? {
+            style = style.fg(fg);
+        }
+        if let Some(bg) = color(&self.bg)? {
+            style = style.bg(bg);
+        }
+        for (enabled, modifier) in [
+            (self.bold, Modifier::BOLD),
+            (self.italic, Modifier::ITALIC),
+            (self.underline, Modifier::UNDERLINED),
+            (self.reversed, Modifier::REVERSED),
+        ] {
+            if enabled {
+                style = style.add_modifier(modifier);
+            }
+        }
+        Ok(style)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ThemeFile {
+    pub name: String,
+    #[serde(default = "default_base")]
+    pub base: String,
+    #[serde(default)]
+    pub styles: BTreeMap<String, StyleSpec>,
+}
+
+fn default_base() -> String {
+    "dark".to_string()
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ThemeConfig {
+    #[serde(default)]
+    pub theme: Option<String>,
+}
+
+impl ThemeConfig {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_yaml::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let content = serde_yaml::to_string(self).map_err(io::Error::other)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, content)
+    }
+}
+
+pub struct Themes {
+    themes: Vec<Theme>,
+    active: usize,
+}
+
+impl Default for Themes {
+    fn default() -> Self {
+        Self { themes: BUILT_IN.iter().filter_map(|name| Theme::built_in(name)).collect(), active: 0 }
+    }
+}
+
+impl Themes {
+    pub fn load(dir: &Path, selected: Option<&str>) -> (Self, Vec<String>) {
+        let mut themes = Self::default();
+        let mut warnings = Vec::new();
+        let mut paths = fs::read_dir(dir)
+            .map(|entries| entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect::<Vec<_>>())
+            .unwrap_or_default();
+        paths.sort();
+        for path in paths {
+            if !matches!(path.extension().and_then(|ext| ext.to_str()), Some("yaml" | "yml")) {
+                continue;
+            }
+            let file = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| serde_yaml::from_str::<ThemeFile>(&content).map_err(|e| e.to_string()));
+            match file {
+                Ok(file) => themes.add(Theme::from_file(file, &mut warnings)),
+                Err(e) => warnings.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+        if let Some(name) = selected {
+            if !themes.select(name) {
+                warnings.push(format!("unknown theme {}", name));
+            }
+        }
+        (themes, warnings)
+    }
+
+    pub fn add(&mut self, theme: Theme) {
+        match self.themes.iter().position(|existing| existing.name == theme.name) {
+            Some(index) => self.themes[index] = theme,
+            None => self.themes.push(theme),
+        }
+    }
+
+    pub fn active(&self) -> &Theme {
+        &self.themes[self.active]
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.themes.iter().map(|theme| theme.name.as_str())
+    }
+
+    pub fn select(&mut self, name: &str) -> bool {
+        match self.themes.iter().position(|theme| theme.name == name) {
+            Some(index) => {
+                self.active = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn next(&mut self) -> &Theme {
+        self.active = (self.active + 1) % self.themes.len();
+        self.active()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Theme, Themes};
+    use crate::snapshot;
+    use ratatui::style::{Color, Modifier};
+    use std::fs;
+
+    #[test]
+    fn loads_user_themes_over_a_base() {
+        let dir = snapshot::temp_dir("themes");
+        fs::write(
+            dir.join("solarized.yaml"),
+            "name: solarized\nbase: light\nstyles:\n  highlight: {fg: \"#b58900\", bold: true}\n  border: {fg: nope}\n  glow: {fg: red}\n",
+        )
+        .unwrap();
+        fs::write(dir.join("dark.yml"), "name: dark\nstyles:\n  error: {fg: magenta, underline: true}\n").unwrap();
+        fs::write(dir.join("notes.txt"), "not a theme").unwrap();
+
+        let (themes, warnings) = Themes::load(&dir, Some("solarized"));
+        assert_eq!(warnings, vec!["theme solarized: border: unknown color nope", "theme solarized: unknown style glow"]);
+        assert_eq!(themes.names().collect::<Vec<_>>(), vec!["dark", "light", "high-contrast", "solarized"]);
+
+        let theme = themes.active();
+        assert_eq!(theme.highlight.fg, Some(Color::Rgb(0xb5, 0x89, 0x00)));
+        assert!(theme.highlight.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(theme.border, Theme::light().border);
+        assert_eq!(theme.accent, Theme::light().accent);
+
+        let mut themes = themes;
+        assert!(themes.select("dark"));
+        assert_eq!(themes.active().error.fg, Some(Color::Magenta));
+        assert_eq!(themes.active().success, Theme::dark().success);
+        assert_eq!(themes.next().name, "light");
+    }
+
+    #[test]
+    fn warns_about_unknown_selection() {
+        let (themes, warnings) = Themes::load(&snapshot::temp_dir("themes-missing"), Some("neon"));
+        assert_eq!(warnings, vec!["unknown theme neon"]);
+        assert_eq!(themes.active().name, "dark");
+    }
+}
@@ -1,8 +1,96 @@
+use crate::chat::{ChatEvent, ChatRequest, ChatResponse};
+use crate::editor::EditTarget;
+use crate::keymap::Keymap;
+use crate::notify::{Notifications, Severity};
+use crate::theme::Theme;
 use ratatui::Frame;
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyEvent, MouseEvent};
+
+pub enum Action {
+    None,
+    Copy(String),
+    Edit(EditTarget),
+    Navigate(char),
+    Notify(Severity, String),
+    Quit,
+    Send(ChatRequest),
+}
+
+pub enum Update {
+    Chat(ChatEvent),
+    Finished(Result<(String, ChatResponse), String>),
+    Edited(Result<String, String>),
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Status {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub context_tokens: Option<u64>,
+    pub busy: bool,
+}
+
+pub struct PaletteEntry {
+    pub label: String,
+    pub command: String,
+}
 
 pub trait View {
-    fn render(&self, f: &mut Frame, area: ratatui::layout::Rect, info_message: &str);
+    fn title(&self) -> &'static str;
+
+    fn shortcut(&self) -> char;
+
+    /// The `(action, label)` pairs of the current keymap scope shown as key hints under the header.
+    fn hints(&self) -> &'static [(&'static str, &'static str)] {
+        &[]
+    }
+
+    /// Called once the keymap is final, for views that render key hints of their own.
+    fn set_keymap(&mut self, _keymap: &Keymap) {}
+
+    fn keymap_scope(&self) -> &'static str;
+
+    fn render(&self, f: &mut Frame, area: ratatui::layout::Rect, theme: &Theme);
+
+    fn handle_input(&mut self, key: KeyEvent, binding: Option<&str>, notifications: &mut Notifications) -> Action;
+
+    fn handle_mouse(&mut self, _event: MouseEvent, _notifications: &mut Notifications) -> Action {
+        Action::None
+    }
+
+    fn handle_paste(&mut self, _text: &str) {}
+
+    fn palette_entries(&self) -> Vec<PaletteEntry> {
+        Vec::new()
+    }
+
+    fn run_command(&mut self, _command: &str, _notifications: &mut Notifications) -> Action {
+        Action::None
+    }
+
+    fn status(&self) -> Status {
+        Status::default()
+    }
+
+    fn split(&self) -> Option<u16> {
+        None
+    }
+
+    fn set_split(&mut self, _split: u16) {}
+
+    fn wants_raw_input(&self) -> bool {
+        false
+    }
+
+    fn on_enter(&mut self) {}
+
+    fn on_leave(&mut self) {}
+
+    fn tick(&mut self) -> Action {
+        Action::None
+    }
 
-    fn handle_input(&mut self, key: KeyEvent, info_message: &mut String);
+    fn update(&mut self, _update: Update) -> Action {
+        Action::None
+    }
 }
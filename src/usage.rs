@@ -0,0 +1,269 @@
+use chrono::{DateTime, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+pub type SharedLedger = Rc<RefCell<UsageLedger>>;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Price {
+    pub prompt_per_million: f64,
+    pub completion_per_million: f64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Budget {
+    pub daily: Option<f64>,
+    pub monthly: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UsageConfig {
+    #[serde(default)]
+    pub prices: BTreeMap<String, Price>,
+    #[serde(default)]
+    pub budget: Budget,
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        let prices = [
+            ("gpt-4o-mini", 0.15, 0.60),
+            ("gpt-4o", 2.50, 10.00),
+            ("gpt-4.1-mini", 0.40, 1.60),
+            ("gpt-4.1", 2.00, 8.00),
+            ("claude-3-5-haiku", 0.80, 4.00),
+            ("claude-3-5-sonnet", 3.00, 15.00),
+            ("claude-3-7-sonnet", 3.00, 15.00),
+            ("gemini-1.5-flash", 0.075, 0.30),
+            ("gemini-1.5-pro", 1.25, 5.00),
+            ("llama-3.1-8b-instant", 0.05, 0.08),
+            ("llama-3.3-70b-versatile", 0.59, 0.79),
+        ]
+        .into_iter()
+        .map(|(model, prompt, completion)| {
+            (model.to_string(), Price { prompt_per_million: prompt, completion_per_million: completion })
+        })
+        .collect();
+        Self { prices, budget: Budget::default() }
+    }
+}
+
+impl UsageConfig {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_yaml::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn price(&self, model: &str) -> Option<Price> {
+        self.prices.get(model).copied().or_else(|| {
+            self.prices
+                .iter()
+                .filter(|(name, _)| model.starts_with(name.as_str()))
+                .max_by_key(|(name, _)| name.len())
+                .map(|(_, price)| *price)
+        })
+    }
+
+    pub fn cost(&self, model: &str, usage: &Usage) -> f64 {
+        self.price(model)
+            .map(|price| {
+                (usage.prompt_tokens as f64 * price.prompt_per_million
+                    + usage.completion_tokens as f64 * price.completion_per_million)
+                    / 1_000_000.0
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub timestamp: i64,
+    pub provider: String,
+    pub model: String,
+    pub conversation: String,
+    pub usage: Usage,
+    pub cost: f64,
+}
+
+impl UsageRecord {
+    pub fn day(&self) -> String {
+        format_timestamp(self.timestamp, "%Y-%m-%d")
+    }
+
+    pub fn month(&self) -> String {
+        format_timestamp(self.timestamp, "%Y-%m")
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Totals {
+    pub requests: usize,
+    pub usage: Usage,
+    pub cost: f64,
+}
+
+impl Totals {
+    fn add(&mut self, record: &UsageRecord) {
+        self.requests += 1;
+        self.usage.prompt_tokens += record.usage.prompt_tokens;
+        self.usage.completion_tokens += record.usage.completion_tokens;
+        self.cost += record.cost;
+    }
+}
+
+pub struct UsageLedger {
+    pub config: UsageConfig,
+    records: Vec<UsageRecord>,
+    path: PathBuf,
+}
+
+impl UsageLedger {
+    pub fn load(config_path: &Path, path: PathBuf) -> Self {
+        let config = UsageConfig::load(config_path).unwrap_or_default();
+        let records = fs::read_to_string(&path)
+            .map(|content| content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+            .unwrap_or_default();
+        Self { config, records, path }
+    }
+
+    pub fn shared(self) -> SharedLedger {
+        Rc::new(RefCell::new(self))
+    }
+
+    pub fn record(&mut self, provider: &str, model: &str, conversation: &str, usage: Usage) -> io::Result<UsageRecord> {
+        let record = UsageRecord {
+            timestamp: Local::now().timestamp(),
+            provider: provider.to_string(),
+            model: model.to_string(),
+            conversation: conversation.to_string(),
+            usage,
+            cost: self.config.cost(model, &usage),
+        };
+        self.records.push(record.clone());
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&record)?)?;
+        Ok(record)
+    }
+
+    pub fn check_budget(&self) -> Result<(), String> {
+        self.check_budget_at(Local::now())
+    }
+
+    fn check_budget_at(&self, now: DateTime<Local>) -> Result<(), String> {
+        let today = now.format("%Y-%m-%d").to_string();
+        let month = now.format("%Y-%m").to_string();
+        if let Some(limit) = self.config.budget.daily {
+            let spent = self.total_where(|record| record.day() == today).cost;
+            if spent >= limit {
+                return Err(format!("daily budget of ${:.2} reached (${:.4} spent)", limit, spent));
+            }
+        }
+        if let Some(limit) = self.config.budget.monthly {
+            let spent = self.total_where(|record| record.month() == month).cost;
+            if spent >= limit {
+                return Err(format!("monthly budget of ${:.2} reached (${:.4} spent)", limit, spent));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn total_where(&self, filter: impl Fn(&UsageRecord) -> bool) -> Totals {
+        let mut totals = Totals::default();
+        self.records.iter().filter(|record| filter(record)).for_each(|record| totals.add(record));
+        totals
+    }
+
+    pub fn group_by(&self, key: impl Fn(&UsageRecord) -> String) -> BTreeMap<String, Totals> {
+        let mut groups: BTreeMap<String, Totals> = BTreeMap::new();
+        for record in &self.records {
+            groups.entry(key(record)).or_default().add(record);
+        }
+        groups
+    }
+}
+
+fn format_timestamp(timestamp: i64, format: &str) -> String {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .map(|time| time.format(format).to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Budget, Usage, UsageConfig, UsageLedger, UsageRecord};
+    use crate::snapshot;
+    use chrono::{Local, TimeZone};
+    use std::fs;
+
+    fn record(provider: &str, (year, month, day): (i32, u32, u32), cost: f64) -> UsageRecord {
+        UsageRecord {
+            timestamp: Local.with_ymd_and_hms(year, month, day, 12, 0, 0).unwrap().timestamp(),
+            provider: provider.to_string(),
+            model: "gpt-4o".to_string(),
+            conversation: "Conversation 1".to_string(),
+            usage: Usage { prompt_tokens: 1_000, completion_tokens: 500 },
+            cost,
+        }
+    }
+
+    #[test]
+    fn prices_models_by_longest_prefix() {
+        let config = UsageConfig::default();
+        let usage = Usage { prompt_tokens: 1_000_000, completion_tokens: 100_000 };
+        assert_eq!(config.cost("gpt-4o", &usage), 2.50 + 1.00);
+        assert_eq!(config.cost("gpt-4o-mini-2024-07-18", &usage), 0.15 + 0.06);
+        assert_eq!(config.cost("gpt-4o-2024-08-06", &usage), 2.50 + 1.00);
+        assert_eq!(config.cost("unknown-model", &usage), 0.0);
+    }
+
+    #[test]
+    fn rolls_budgets_over_by_day_and_month() {
+        let dir = snapshot::temp_dir("usage-budget");
+        let records = [record("Work", (2026, 2, 28), 4.0), record("Work", (2026, 3, 14), 2.0), record("Home", (2026, 3, 15), 0.5)];
+        let lines: Vec<String> = records.iter().map(|record| serde_json::to_string(record).unwrap()).collect();
+        fs::write(dir.join("usage.jsonl"), lines.join("\n")).unwrap();
+        let mut ledger = UsageLedger::load(&dir.join("usage.yaml"), dir.join("usage.jsonl"));
+        ledger.config.budget = Budget { daily: Some(1.0), monthly: Some(3.0) };
+
+        let at = |day| Local.with_ymd_and_hms(2026, 3, day, 18, 0, 0).unwrap();
+        assert_eq!(ledger.check_budget_at(at(15)), Ok(()));
+        assert_eq!(ledger.check_budget_at(at(14)), Err("daily budget of $1.00 reached ($2.0000 spent)".to_string()));
+
+        ledger.config.budget.monthly = Some(2.5);
+        assert_eq!(ledger.check_budget_at(at(15)), Err("monthly budget of $2.50 reached ($2.5000 spent)".to_string()));
+        assert_eq!(ledger.check_budget_at(Local.with_ymd_and_hms(2026, 4, 1, 9, 0, 0).unwrap()), Ok(()));
+    }
+
+    #[test]
+    fn appends_records_to_the_log() {
+        let dir = snapshot::temp_dir("usage-record");
+        let mut ledger = UsageLedger::load(&dir.join("usage.yaml"), dir.join("logs").join("usage.jsonl"));
+        let record = ledger.record("Work", "gpt-4o-mini", "Conversation 1", Usage { prompt_tokens: 2_000_000, completion_tokens: 0 }).unwrap();
+        assert_eq!(record.cost, 0.30);
+
+        let reloaded = UsageLedger::load(&dir.join("usage.yaml"), dir.join("logs").join("usage.jsonl"));
+        let totals = reloaded.group_by(|record| record.provider.clone());
+        assert_eq!(totals["Work"].requests, 1);
+        assert_eq!(totals["Work"].usage.prompt_tokens, 2_000_000);
+    }
+}
@@ -0,0 +1,134 @@
+use crate::notify::Notifications;
+use crate::theme::Theme;
+use crate::traits::{Action, View};
+use crate::usage::{SharedLedger, Totals};
+use chrono::Local;
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+use std::collections::BTreeMap;
+
+pub struct UsageView {
+    ledger: SharedLedger,
+}
+
+impl UsageView {
+    pub fn new(ledger: SharedLedger) -> Self {
+        Self { ledger }
+    }
+
+    fn totals_lines(groups: BTreeMap<String, Totals>, newest_first: bool) -> Vec<Line<'static>> {
+        let mut rows: Vec<(String, Totals)> = groups.into_iter().collect();
+        if newest_first {
+            rows.reverse();
+        }
+        rows.into_iter()
+            .map(|(key, totals)| {
+                Line::raw(format!(
+                    "{:<24} {:>5} req {:>9} in {:>9} out  ${:.4}",
+                    key, totals.requests, totals.usage.prompt_tokens, totals.usage.completion_tokens, totals.cost
+                ))
+            })
+            .collect()
+    }
+}
+
+impl View for UsageView {
+    fn title(&self) -> &'static str {
+        "Usage View"
+    }
+
+    fn shortcut(&self) -> char {
+        '4'
+    }
+
+    fn render(&self, f: &mut Frame, area: ratatui::layout::Rect, theme: &Theme) {
+        let ledger = self.ledger.borrow();
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        let month = Local::now().format("%Y-%m").to_string();
+        let today_totals = ledger.total_where(|record| record.day() == today);
+        let month_totals = ledger.total_where(|record| record.month() == month);
+        let budget = |spent: f64, limit: Option<f64>| match limit {
+            Some(limit) => format!("${:.4} of ${:.2}", spent, limit),
+            None => format!("${:.4} (no limit)", spent),
+        };
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(4), Constraint::Min(0)])
+            .split(area);
+        let cols = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(34), Constraint::Percentage(33), Constraint::Percentage(33)])
+            .split(rows[1]);
+
+        let summary = Paragraph::new(vec![
+            Line::from(vec![
+                Span::styled("Today:      ", theme.highlight),
+                Span::raw(budget(today_totals.cost, ledger.config.budget.daily)),
+            ]),
+            Line::from(vec![
+                Span::styled("This month: ", theme.highlight),
+                Span::raw(budget(month_totals.cost, ledger.config.budget.monthly)),
+            ]),
+        ])
+        .block(theme.block("Budget"));
+        f.render_widget(summary, rows[0]);
+
+        let daily = Paragraph::new(Self::totals_lines(ledger.group_by(|record| record.day()), true))
+            .block(theme.block("Daily"));
+        f.render_widget(daily, cols[0]);
+
+        let monthly = Paragraph::new(Self::totals_lines(ledger.group_by(|record| record.month()), true))
+            .block(theme.block("Monthly"));
+        f.render_widget(monthly, cols[1]);
+
+        let providers = Paragraph::new(Self::totals_lines(ledger.group_by(|record| record.provider.clone()), false))
+            .block(theme.block("Providers"));
+        f.render_widget(providers, cols[2]);
+    }
+
+    fn keymap_scope(&self) -> &'static str {
+        "usage"
+    }
+
+    fn handle_input(&mut self, _key: crossterm::event::KeyEvent, _binding: Option<&str>, _notifications: &mut Notifications) -> Action {
+        Action::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UsageView;
+    use crate::snapshot::{self, Harness};
+    use crate::usage::{Budget, Usage, UsageLedger, UsageRecord};
+    use chrono::{Local, TimeZone};
+    use std::fs;
+
+    #[test]
+    fn renders_totals_by_day_month_and_provider() {
+        let dir = snapshot::temp_dir("usage-view");
+        let lines: Vec<String> = [("Work", 1, 30, 0.25), ("Work", 2, 1, 0.5), ("Home", 2, 1, 0.125)]
+            .into_iter()
+            .map(|(provider, month, day, cost)| {
+                serde_json::to_string(&UsageRecord {
+                    timestamp: Local.with_ymd_and_hms(2025, month, day, 12, 0, 0).unwrap().timestamp(),
+                    provider: provider.to_string(),
+                    model: "gpt-4o".to_string(),
+                    conversation: "Conversation 1".to_string(),
+                    usage: Usage { prompt_tokens: 1_200, completion_tokens: 300 },
+                    cost,
+                })
+                .unwrap()
+            })
+            .collect();
+        fs::write(dir.join("usage.jsonl"), lines.join("\n")).unwrap();
+        let mut ledger = UsageLedger::load(&dir.join("usage.yaml"), dir.join("usage.jsonl"));
+        ledger.config.budget = Budget { daily: Some(1.0), monthly: None };
+
+        Harness::new(UsageView::new(ledger.shared()), 220, 10).assert_snapshot("usage_view_totals");
+    }
+}
@@ -0,0 +1,614 @@
+use crate::clipboard;
+use crate::mouse;
+use crate::palette;
+use crate::theme::Theme;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+use ratatui::{
+    layout::{Alignment, Position, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+use std::cell::Cell;
+
+#[derive(Clone, Debug, Default)]
+pub struct TextInput {
+    value: String,
+    cursor: usize,
+    multiline: bool,
+    mask: Option<fn(&str) -> String>,
+}
+
+impl TextInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn multiline() -> Self {
+        Self { multiline: true, ..Self::default() }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.cursor = self.value.len();
+    }
+
+    pub fn take(&mut self) -> String {
+        self.cursor = 0;
+        std::mem::take(&mut self.value)
+    }
+
+    pub fn set_mask(&mut self, mask: Option<fn(&str) -> String>) {
+        self.mask = mask;
+    }
+
+    pub fn insert_str(&mut self, text: &str) {
+        let text = if self.multiline { text.replace("\r\n", "\n") } else { text.replace(['\r', '\n'], " ") };
+        self.value.insert_str(self.cursor, &text);
+        self.cursor += text.len();
+    }
+
+    fn line_start(&self) -> usize {
+        self.value[..self.cursor].rfind('\n').map(|index| index + 1).unwrap_or(0)
+    }
+
+    fn line_end(&self) -> usize {
+        self.value[self.cursor..].find('\n').map(|index| self.cursor + index).unwrap_or(self.value.len())
+    }
+
+    fn prev_boundary(&self) -> usize {
+        self.value[..self.cursor].char_indices().next_back().map(|(index, _)| index).unwrap_or(0)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.value[self.cursor..].chars().next().map(|c| self.cursor + c.len_utf8()).unwrap_or(self.cursor)
+    }
+
+    fn word_start(&self) -> usize {
+        let before = self.value[..self.cursor].trim_end();
+        before.rfind(char::is_whitespace).map(|index| index + 1).unwrap_or(0)
+    }
+
+    fn cut(&mut self, start: usize, end: usize) {
+        if start < end {
+            clipboard::copy(&self.value[start..end]);
+            self.value.replace_range(start..end, "");
+            self.cursor = start;
+        }
+    }
+
+    fn move_line(&mut self, down: bool) {
+        let column = self.value[self.line_start()..self.cursor].chars().count();
+        let start = if down {
+            let end = self.line_end();
+            if end == self.value.len() {
+                return;
+            }
+            end + 1
+        } else {
+            let start = self.line_start();
+            if start == 0 {
+                return;
+            }
+            self.value[..start - 1].rfind('\n').map(|index| index + 1).unwrap_or(0)
+        };
+        let line = self.value[start..].split('\n').next().unwrap_or_default();
+        self.cursor = start + line.chars().take(column).map(char::len_utf8).sum::<usize>();
+    }
+
+    pub fn handle_input(&mut self, key: KeyEvent, binding: Option<&str>) -> bool {
+        if binding.is_some() {
+            return false;
+        }
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('a') if control => self.cursor = self.line_start(),
+            KeyCode::Char('e') if control => self.cursor = self.line_end(),
+            KeyCode::Char('k') if control => self.cut(self.cursor, self.line_end()),
+            KeyCode::Char('u') if control => self.cut(self.line_start(), self.cursor),
+            KeyCode::Char('w') if control => self.cut(self.word_start(), self.cursor),
+            KeyCode::Char('y') if control => self.insert_str(&clipboard::paste()),
+            KeyCode::Char(_) if control || key.modifiers.contains(KeyModifiers::ALT) => return false,
+            KeyCode::Char(c) => {
+                self.value.insert(self.cursor, c);
+                self.cursor += c.len_utf8();
+            }
+            KeyCode::Enter if self.multiline => {
+                self.value.insert(self.cursor, '\n');
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                let start = self.prev_boundary();
+                self.value.replace_range(start..self.cursor, "");
+                self.cursor = start;
+            }
+            KeyCode::Delete if self.cursor < self.value.len() => {
+                let end = self.next_boundary();
+                self.value.replace_range(self.cursor..end, "");
+            }
+            KeyCode::Left => self.cursor = self.prev_boundary(),
+            KeyCode::Right => self.cursor = self.next_boundary(),
+            KeyCode::Home => self.cursor = self.line_start(),
+            KeyCode::End => self.cursor = self.line_end(),
+            KeyCode::Up if self.multiline => self.move_line(false),
+            KeyCode::Down if self.multiline => self.move_line(true),
+            KeyCode::Backspace | KeyCode::Delete => {}
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn display(&self) -> String {
+        match self.mask {
+            Some(mask) => mask(&self.value),
+            None => self.value.clone(),
+        }
+    }
+
+    pub fn lines(&self) -> u16 {
+        self.value.split('\n').count() as u16
+    }
+
+    fn cursor_position(&self) -> (u16, u16) {
+        let before = &self.value[..self.cursor];
+        let row = before.matches('\n').count();
+        let column = before[self.line_start()..].chars().count();
+        (column as u16, row as u16)
+    }
+
+    pub fn render(&self, f: &mut Frame, area: Rect, style: Style, focused: bool) {
+        let (column, row) = self.cursor_position();
+        let scroll = (row.saturating_sub(area.height.saturating_sub(1)), column.saturating_sub(area.width.saturating_sub(1)));
+        f.render_widget(Paragraph::new(self.display()).style(style).scroll(scroll), area);
+        if focused && area.width > 0 && area.height > 0 {
+            f.set_cursor_position(Position { x: area.x + column - scroll.1, y: area.y + row - scroll.0 });
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Checkbox {
+    pub checked: bool,
+}
+
+impl Checkbox {
+    pub fn handle_input(&mut self, key: KeyEvent, binding: Option<&str>) -> bool {
+        match (binding, key.code) {
+            (Some("toggle"), _) | (None, KeyCode::Char(' ')) => {
+                self.checked = !self.checked;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn display(&self) -> &'static str {
+        if self.checked {
+            "[x]"
+        } else {
+            "[ ]"
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Dropdown {
+    options: Vec<String>,
+    selected: usize,
+    highlighted: usize,
+    open: bool,
+    popup: Cell<Rect>,
+}
+
+impl Dropdown {
+    pub fn new(options: Vec<String>) -> Self {
+        Self { options, ..Self::default() }
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn value(&self) -> &str {
+        self.options.get(self.selected).map(String::as_str).unwrap_or_default()
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.selected = index.min(self.options.len().saturating_sub(1));
+        self.highlighted = self.selected;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+        self.highlighted = self.selected;
+    }
+
+    pub fn handle_input(&mut self, key: KeyEvent, binding: Option<&str>) -> bool {
+        if !self.open {
+            if matches!((binding, key.code), (Some("confirm" | "toggle"), _) | (None, KeyCode::Char(' '))) {
+                self.open();
+                return true;
+            }
+            return false;
+        }
+        match binding {
+            Some("select_next") => self.highlighted = (self.highlighted + 1).min(self.options.len().saturating_sub(1)),
+            Some("select_prev") => self.highlighted = self.highlighted.saturating_sub(1),
+            Some("confirm") => {
+                self.selected = self.highlighted;
+                self.open = false;
+            }
+            Some("cancel") => self.open = false,
+            _ => {}
+        }
+        true
+    }
+
+    pub fn handle_mouse(&mut self, event: &MouseEvent) -> bool {
+        if !self.open {
+            return false;
+        }
+        if let Some(index) = mouse::list_row(self.popup.get(), 0, self.options.len(), event) {
+            self.select(index);
+            self.open = false;
+        } else if matches!(event.kind, MouseEventKind::Down(_)) {
+            self.open = false;
+        }
+        true
+    }
+
+    pub fn render_popup(&self, f: &mut Frame, anchor: Rect, theme: &Theme) {
+        if !self.open {
+            return;
+        }
+        let area = f.area();
+        let width = (self.options.iter().map(|option| option.chars().count()).max().unwrap_or_default() as u16 + 4).min(area.width);
+        let height = (self.options.len() as u16 + 2).min(area.height);
+        let below = anchor.bottom();
+        let y = if below + height <= area.bottom() { below } else { anchor.y.saturating_sub(height).max(area.y) };
+        let x = anchor.x.min(area.right().saturating_sub(width));
+        let popup = Rect { x, y, width, height };
+        self.popup.set(popup);
+        let items: Vec<ListItem> = self.options.iter().map(|option| ListItem::new(option.as_str())).collect();
+        let list = List::new(items).block(theme.block("")).style(theme.text).highlight_style(theme.highlight).highlight_symbol("> ");
+        f.render_widget(Clear, popup);
+        f.render_stateful_widget(list, popup, &mut ListState::default().with_selected(Some(self.highlighted)));
+    }
+}
+
+pub enum Field {
+    Text(TextInput),
+    Checkbox(Checkbox),
+    Dropdown(Dropdown),
+}
+
+impl Field {
+    fn display(&self) -> String {
+        match self {
+            Field::Text(input) => input.display(),
+            Field::Checkbox(checkbox) => checkbox.display().to_string(),
+            Field::Dropdown(dropdown) => format!("{} ▾", dropdown.value()),
+        }
+    }
+}
+
+pub enum FormEvent {
+    None,
+    Submit,
+    Cancel,
+}
+
+pub struct Form {
+    fields: Vec<(&'static str, Field)>,
+    focus: usize,
+    area: Cell<Rect>,
+    offset: Cell<usize>,
+}
+
+impl Form {
+    pub fn new(fields: Vec<(&'static str, Field)>) -> Self {
+        Self { fields, focus: 0, area: Cell::default(), offset: Cell::default() }
+    }
+
+    pub fn set_focus(&mut self, index: usize) {
+        if index < self.fields.len() {
+            self.focus = index;
+        }
+    }
+
+    pub fn focus_next(&mut self) {
+        self.focus = (self.focus + 1) % self.fields.len();
+    }
+
+    pub fn focus_prev(&mut self) {
+        self.focus = (self.focus + self.fields.len() - 1) % self.fields.len();
+    }
+
+    pub fn get(&self, label: &str) -> Option<&Field> {
+        self.fields.iter().find(|(candidate, _)| *candidate == label).map(|(_, field)| field)
+    }
+
+    pub fn get_mut(&mut self, label: &str) -> Option<&mut Field> {
+        self.fields.iter_mut().find(|(candidate, _)| *candidate == label).map(|(_, field)| field)
+    }
+
+    pub fn selected(&self, label: &str) -> usize {
+        match self.get(label) {
+            Some(Field::Dropdown(dropdown)) => dropdown.selected(),
+            _ => 0,
+        }
+    }
+
+    pub fn text(&self, label: &str) -> &str {
+        match self.get(label) {
+            Some(Field::Text(input)) => input.value(),
+            Some(Field::Dropdown(dropdown)) => dropdown.value(),
+            _ => "",
+        }
+    }
+
+    pub fn checked(&self, label: &str) -> bool {
+        matches!(self.get(label), Some(Field::Checkbox(checkbox)) if checkbox.checked)
+    }
+
+    pub fn focused_mut(&mut self) -> &mut Field {
+        &mut self.fields[self.focus].1
+    }
+
+    pub fn handle_input(&mut self, key: KeyEvent, binding: Option<&str>) -> FormEvent {
+        if let Field::Dropdown(dropdown) = self.focused_mut() {
+            if dropdown.is_open() || binding == Some("toggle") {
+                dropdown.handle_input(key, binding);
+                return FormEvent::None;
+            }
+        }
+        match binding {
+            Some("next_field" | "select_next") => self.focus_next(),
+            Some("prev_field" | "select_prev") => self.focus_prev(),
+            Some("confirm") => return FormEvent::Submit,
+            Some("cancel") => return FormEvent::Cancel,
+            _ => {
+                let _ = match self.focused_mut() {
+                    Field::Text(input) => input.handle_input(key, binding),
+                    Field::Checkbox(checkbox) => checkbox.handle_input(key, binding),
+                    Field::Dropdown(dropdown) => dropdown.handle_input(key, binding),
+                };
+            }
+        }
+        FormEvent::None
+    }
+
+    pub fn handle_paste(&mut self, text: &str) {
+        if let Field::Text(input) = self.focused_mut() {
+            input.insert_str(text);
+        }
+    }
+
+    pub fn handle_mouse(&mut self, event: &MouseEvent) {
+        if let Field::Dropdown(dropdown) = self.focused_mut() {
+            if dropdown.handle_mouse(event) {
+                return;
+            }
+        }
+        if let Some(index) = mouse::list_row(self.area.get(), self.offset.get(), self.fields.len(), event) {
+            if index == self.focus {
+                match self.focused_mut() {
+                    Field::Checkbox(checkbox) => checkbox.checked = !checkbox.checked,
+                    Field::Dropdown(dropdown) => dropdown.open(),
+                    Field::Text(_) => {}
+                }
+            }
+            self.focus = index;
+        }
+    }
+
+    pub fn render(&self, f: &mut Frame, area: Rect, title: &str, theme: &Theme) {
+        let items: Vec<ListItem> = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(index, (label, field))| {
+                ListItem::new(Span::styled(
+                    format!("{}: {}", label, field.display()),
+                    if index == self.focus { theme.highlight } else { theme.text },
+                ))
+            })
+            .collect();
+        let list = List::new(items).block(theme.block(title)).highlight_style(theme.highlight).highlight_symbol(">> ");
+        let mut state = ListState::default().with_selected(Some(self.focus));
+        f.render_stateful_widget(list, area, &mut state);
+        self.area.set(area);
+        self.offset.set(state.offset());
+
+        let (label, field) = &self.fields[self.focus];
+        let row = Rect {
+            x: area.x + 4 + label.chars().count() as u16 + 2,
+            y: area.y + 1 + (self.focus - state.offset()) as u16,
+            width: area.width.saturating_sub(label.chars().count() as u16 + 7),
+            height: 1,
+        };
+        match field {
+            Field::Text(input) => input.render(f, row, theme.highlight, true),
+            Field::Dropdown(dropdown) => dropdown.render_popup(f, row, theme),
+            Field::Checkbox(_) => {}
+        }
+    }
+}
+
+pub enum ModalOutcome {
+    Open,
+    Confirmed,
+    Cancelled,
+}
+
+pub struct Modal {
+    title: String,
+    message: String,
+    confirm: bool,
+    accept: bool,
+    buttons: Cell<(Rect, Rect)>,
+}
+
+impl Modal {
+    pub fn confirm(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { title: title.into(), message: message.into(), confirm: true, accept: false, buttons: Cell::default() }
+    }
+
+    pub fn alert(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { confirm: false, accept: true, ..Self::confirm(title, message) }
+    }
+
+    pub fn handle_input(&mut self, _key: KeyEvent, binding: Option<&str>) -> ModalOutcome {
+        match binding {
+            Some("accept") => ModalOutcome::Confirmed,
+            Some("reject" | "cancel") => ModalOutcome::Cancelled,
+            Some("confirm") if self.accept => ModalOutcome::Confirmed,
+            Some("confirm") => ModalOutcome::Cancelled,
+            Some("toggle") if self.confirm => {
+                self.accept = !self.accept;
+                ModalOutcome::Open
+            }
+            _ => ModalOutcome::Open,
+        }
+    }
+
+    pub fn handle_mouse(&mut self, event: &MouseEvent) -> ModalOutcome {
+        let (accept, reject) = self.buttons.get();
+        if mouse::clicked(accept, event) {
+            ModalOutcome::Confirmed
+        } else if mouse::clicked(reject, event) {
+            ModalOutcome::Cancelled
+        } else {
+            ModalOutcome::Open
+        }
+    }
+
+    pub fn render(&self, f: &mut Frame, area: Rect, theme: &Theme) {
+        let width = (self.message.chars().count() as u16 + 4).clamp(30, 60).min(area.width);
+        let lines = (self.message.chars().count() as u16).div_ceil(width.saturating_sub(2).max(1));
+        let popup = palette::centered(area, width, lines + 4);
+        f.render_widget(Clear, popup);
+        let block = theme.block(self.title.as_str());
+        let inner = block.inner(popup);
+        f.render_widget(block, popup);
+        f.render_widget(Paragraph::new(self.message.as_str()).style(theme.text).wrap(Wrap { trim: true }), inner);
+
+        let labels: &[&str] = if self.confirm { &[" Yes ", " No "] } else { &[" OK "] };
+        let styles = [self.accept, !self.accept].map(|active| if active { theme.header_active } else { theme.text });
+        let total = labels.iter().map(|label| label.len() as u16 + 2).sum::<u16>() + 2 * (labels.len() as u16 - 1);
+        let mut x = inner.x + inner.width.saturating_sub(total) / 2;
+        let y = inner.bottom().saturating_sub(1);
+        let mut rects = [Rect::default(); 2];
+        let mut spans = Vec::new();
+        for (index, label) in labels.iter().enumerate() {
+            if index > 0 {
+                spans.push(Span::raw("  "));
+                x += 2;
+            }
+            let button = Span::styled(format!("[{}]", label), styles[index]);
+            rects[index] = Rect { x, y, width: button.width() as u16, height: 1 };
+            x += button.width() as u16;
+            spans.push(button);
+        }
+        self.buttons.set((rects[0], rects[1]));
+        let buttons = Rect { y, height: 1, ..inner };
+        f.render_widget(Paragraph::new(Line::from(spans)).alignment(Alignment::Center), buttons);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Checkbox, Dropdown, Field, Form, FormEvent, Modal, ModalOutcome, TextInput};
+    use crate::clipboard;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn type_text(input: &mut TextInput, text: &str) {
+        for c in text.chars() {
+            input.handle_input(key(KeyCode::Char(c)), None);
+        }
+    }
+
+    #[test]
+    fn edits_text_with_cursor_and_clipboard() {
+        let mut input = TextInput::multiline();
+        type_text(&mut input, "héllo wörld");
+        input.handle_input(ctrl('w'), None);
+        assert_eq!(input.value(), "héllo ");
+        input.handle_input(key(KeyCode::Left), None);
+        input.handle_input(key(KeyCode::Left), None);
+        input.handle_input(key(KeyCode::Backspace), None);
+        assert_eq!(input.value(), "hélo ");
+        input.handle_input(ctrl('a'), None);
+        input.handle_input(ctrl('y'), None);
+        assert_eq!(input.value(), "wörldhélo ");
+        assert_eq!(clipboard::paste(), "wörld");
+
+        input.handle_input(ctrl('e'), None);
+        input.handle_input(key(KeyCode::Enter), None);
+        type_text(&mut input, "ab");
+        input.handle_input(key(KeyCode::Up), None);
+        type_text(&mut input, "!");
+        assert_eq!(input.value(), "wö!rldhélo \nab");
+        assert_eq!(input.lines(), 2);
+        assert!(!input.handle_input(key(KeyCode::Enter), Some("send")));
+
+        let mut single = TextInput::new();
+        single.insert_str("one\ntwo");
+        assert_eq!(single.value(), "one two");
+        single.set_mask(Some(|value| "*".repeat(value.len())));
+        assert_eq!(single.display(), "*******");
+    }
+
+    #[test]
+    fn form_moves_focus_and_drives_fields() {
+        let mut form = Form::new(vec![
+            ("Type", Field::Dropdown(Dropdown::new(vec!["OpenAI".to_string(), "Mock".to_string()]))),
+            ("Name", Field::Text(TextInput::new())),
+            ("Enabled", Field::Checkbox(Checkbox::default())),
+        ]);
+        form.handle_input(key(KeyCode::Char(' ')), None);
+        form.handle_input(key(KeyCode::Down), Some("select_next"));
+        form.handle_input(key(KeyCode::Enter), Some("confirm"));
+        assert_eq!(form.text("Type"), "Mock");
+        assert_eq!(form.selected("Type"), 1);
+
+        form.handle_input(key(KeyCode::Tab), Some("next_field"));
+        form.handle_input(key(KeyCode::Char('x')), None);
+        form.handle_input(key(KeyCode::Tab), Some("next_field"));
+        form.handle_input(key(KeyCode::Char(' ')), None);
+        assert_eq!(form.text("Name"), "x");
+        assert!(form.checked("Enabled"));
+        assert!(matches!(form.handle_input(key(KeyCode::Enter), Some("confirm")), FormEvent::Submit));
+        assert!(matches!(form.handle_input(key(KeyCode::Esc), Some("cancel")), FormEvent::Cancel));
+    }
+
+    #[test]
+    fn modal_confirms_only_on_accept() {
+        let mut modal = Modal::confirm("Delete", "Delete provider openai?");
+        assert!(matches!(modal.handle_input(key(KeyCode::Enter), Some("confirm")), ModalOutcome::Cancelled));
+        assert!(matches!(modal.handle_input(key(KeyCode::Tab), Some("toggle")), ModalOutcome::Open));
+        assert!(matches!(modal.handle_input(key(KeyCode::Enter), Some("confirm")), ModalOutcome::Confirmed));
+        assert!(matches!(modal.handle_input(key(KeyCode::Char('n')), Some("reject")), ModalOutcome::Cancelled));
+
+        let mut alert = Modal::alert("Save failed", "disk full");
+        assert!(matches!(alert.handle_input(key(KeyCode::Tab), Some("toggle")), ModalOutcome::Open));
+        assert!(matches!(alert.handle_input(key(KeyCode::Enter), Some("confirm")), ModalOutcome::Confirmed));
+    }
+}